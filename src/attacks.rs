@@ -1,10 +1,16 @@
 use std::ops::{Index, IndexMut};
+use std::sync::LazyLock;
 
-use num_traits::{FromPrimitive, ToPrimitive};
+use num_traits::ToPrimitive;
 use strum::IntoEnumIterator;
 
 use crate::bitboard::Bitboard;
+use crate::board::{SIXTH_RANK, THIRD_RANK};
+use crate::color::Color;
 use crate::direction::{Direction, KnightDirection, RayDirection};
+use crate::magic::{BISHOP_MAGICS, MagicTable, ROOK_MAGICS};
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+use crate::pext::PextTable;
 use crate::square::Square;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -48,6 +54,10 @@ impl IndexMut<Square> for AttackSet {
     }
 }
 
+/// Process-wide attack tables, built once on first use and shared by
+/// move generation and every other consumer of attack sets.
+pub static ATTACKS: LazyLock<Attacks> = LazyLock::new(Attacks::new);
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct Attacks {
     white_pawn: AttackSet,
@@ -55,6 +65,49 @@ pub struct Attacks {
     king: AttackSet,
     knight: AttackSet,
     rays: [AttackSet; 8],
+    between: [AttackSet; 64],
+    line: [AttackSet; 64],
+    sliders: Sliders,
+}
+
+/// The backend used to look up sliding piece attacks. Both produce
+/// identical attack sets and only differ in how tables are indexed.
+///
+/// There is only ever one instance of this per [`Attacks`], so the size
+/// difference between variants does not matter.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, PartialEq, Eq)]
+enum Sliders {
+    Magic {
+        rook: MagicTable,
+        bishop: MagicTable,
+    },
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    Pext { rook: PextTable, bishop: PextTable },
+}
+
+impl Sliders {
+    /// Picks the fastest backend supported by the running CPU.
+    fn new() -> Self {
+        #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+        if let (Some(rook), Some(bishop)) = (
+            PextTable::new(&Direction::ROOK_DIRS),
+            PextTable::new(&Direction::BISHOP_DIRS),
+        ) {
+            return Sliders::Pext { rook, bishop };
+        }
+
+        Sliders::Magic {
+            rook: MagicTable::new(&ROOK_MAGICS, &Direction::ROOK_DIRS),
+            bishop: MagicTable::new(&BISHOP_MAGICS, &Direction::BISHOP_DIRS),
+        }
+    }
+}
+
+impl Default for Attacks {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Attacks {
@@ -69,14 +122,17 @@ impl Attacks {
             king: AttackSet::default(),
             knight: AttackSet::default(),
             rays: [AttackSet::default(); 8],
+            between: [AttackSet::default(); 64],
+            line: [AttackSet::default(); 64],
+            sliders: Sliders::new(),
         };
 
         for sq in Square::iter() {
             let bb = sq.bitboard();
 
-            for (dir_idx, ray_dir) in RayDirection::iter().enumerate() {
+            for ray_dir in RayDirection::iter() {
                 let dir = Direction::Ray(ray_dir);
-                attacks.rays[dir_idx][sq] = bb.fill_all(&dir) & !bb;
+                attacks.rays[ray_dir.index()][sq] = bb.fill_all(&dir) & !bb;
                 attacks.king[sq] |= bb.fill_one(&dir) & !bb;
             }
 
@@ -91,12 +147,103 @@ impl Attacks {
             attacks.black_pawn[sq] |= bb.fill_one(&Direction::Ray(RayDirection::SW));
         }
 
+        // Squares between, and lines through, every pair of aligned squares
+        // can only be computed once every ray is known.
+        for from in Square::iter() {
+            for ray_dir in RayDirection::iter() {
+                let ray = attacks.rays[ray_dir.index()][from];
+                let back = attacks.rays[ray_dir.opposite().index()][from];
+
+                let mut targets = ray;
+                while let Some(to) = targets.pop_lsb() {
+                    let beyond = attacks.rays[ray_dir.index()][to];
+                    attacks.between[from as usize][to] = ray & !beyond & !to.bitboard();
+                    attacks.line[from as usize][to] = ray | back | from.bitboard();
+                }
+            }
+        }
+
         attacks
     }
 
-    /// TODO: Implement pawn attacks (have to take en-passant into play).
-    pub fn pawns(&self, _sq: Square, _blockers: Bitboard) -> Bitboard {
-        todo!("write logic to generate pawn attacks")
+    /// Returns a [`Bitboard`] of the squares attacked by a pawn of `color`
+    /// on `sq`, i.e. the squares diagonally in front of it.
+    ///
+    /// Conversely, the pawns of `color` attacking `sq` are the pawns of
+    /// `color` on the squares attacked by a pawn of the *opposite* color on `sq`.
+    pub fn pawns(&self, sq: Square, color: Color) -> Bitboard {
+        match color {
+            Color::White => self.white_pawn[sq],
+            Color::Black => self.black_pawn[sq],
+        }
+    }
+
+    /// Returns the squares reached by pushing every pawn of `color` in
+    /// `pawns` a single square forward onto an `empty` square.
+    pub fn pawn_pushes(&self, pawns: Bitboard, empty: Bitboard, color: Color) -> Bitboard {
+        (pawns << Direction::Ray(Self::pawn_push_direction(color))) & empty
+    }
+
+    /// Returns the squares reached by pushing every pawn of `color` in
+    /// `pawns` still on its starting rank two squares forward, where both
+    /// squares in front of it are `empty`.
+    pub fn pawn_double_pushes(&self, pawns: Bitboard, empty: Bitboard, color: Color) -> Bitboard {
+        let third_rank = match color {
+            Color::White => Bitboard::new(THIRD_RANK),
+            Color::Black => Bitboard::new(SIXTH_RANK),
+        };
+        let single = self.pawn_pushes(pawns, empty, color) & third_rank;
+        self.pawn_pushes(single, empty, color)
+    }
+
+    /// Returns the squares attacked towards the h-file by every pawn of
+    /// `color` in `pawns`.
+    pub fn pawn_east_attacks(&self, pawns: Bitboard, color: Color) -> Bitboard {
+        pawns << Direction::Ray(Self::pawn_east_direction(color))
+    }
+
+    /// Returns the squares attacked towards the a-file by every pawn of
+    /// `color` in `pawns`.
+    pub fn pawn_west_attacks(&self, pawns: Bitboard, color: Color) -> Bitboard {
+        pawns << Direction::Ray(Self::pawn_west_direction(color))
+    }
+
+    /// Returns every square attacked by at least one pawn of `color` in `pawns`.
+    pub fn pawn_attacks(&self, pawns: Bitboard, color: Color) -> Bitboard {
+        self.pawn_east_attacks(pawns, color) | self.pawn_west_attacks(pawns, color)
+    }
+
+    /// Returns the pawns of `color` in `pawns` which can capture en-passant
+    /// onto the `ep` square.
+    pub fn pawn_en_passant(&self, pawns: Bitboard, ep: Option<Square>, color: Color) -> Bitboard {
+        match ep {
+            Some(sq) => pawns & self.pawns(sq, !color),
+            None => Bitboard::default(),
+        }
+    }
+
+    /// The direction in which pawns of `color` are pushed.
+    pub fn pawn_push_direction(color: Color) -> RayDirection {
+        match color {
+            Color::White => RayDirection::N,
+            Color::Black => RayDirection::S,
+        }
+    }
+
+    /// The direction in which pawns of `color` capture towards the h-file.
+    pub fn pawn_east_direction(color: Color) -> RayDirection {
+        match color {
+            Color::White => RayDirection::NE,
+            Color::Black => RayDirection::SE,
+        }
+    }
+
+    /// The direction in which pawns of `color` capture towards the a-file.
+    pub fn pawn_west_direction(color: Color) -> RayDirection {
+        match color {
+            Color::White => RayDirection::NW,
+            Color::Black => RayDirection::SW,
+        }
     }
 
     /// Returns a [`Bitboard`] representing all squares attacked by a king located
@@ -117,14 +264,22 @@ impl Attacks {
     /// a square given "blockers" (i.e. other pieces which may already
     /// exist on the board).
     pub fn bishop(&self, sq: Square, blockers: Bitboard) -> Bitboard {
-        self.sliding_piece_attacks(sq, blockers, &Direction::BISHOP_DIRS)
+        match &self.sliders {
+            Sliders::Magic { bishop, .. } => bishop.get(sq, blockers),
+            #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+            Sliders::Pext { bishop, .. } => bishop.get(sq, blockers),
+        }
     }
 
     /// Returns a [`Bitboard`] of all squares attacked by a rook on
     /// a square given "blockers" (i.e. other pieces which may already
     /// exist on the board).
     pub fn rook(&self, sq: Square, blockers: Bitboard) -> Bitboard {
-        self.sliding_piece_attacks(sq, blockers, &Direction::ROOK_DIRS)
+        match &self.sliders {
+            Sliders::Magic { rook, .. } => rook.get(sq, blockers),
+            #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+            Sliders::Pext { rook, .. } => rook.get(sq, blockers),
+        }
     }
 
     /// Returns a [`Bitboard`] of all squares attacked by a queen on
@@ -134,31 +289,17 @@ impl Attacks {
         self.rook(sq, blockers) | self.bishop(sq, blockers)
     }
 
-    fn sliding_piece_attacks(
-        &self,
-        sq: Square,
-        blockers: Bitboard,
-        dirs: &[RayDirection; 4],
-    ) -> Bitboard {
-        let mut attacks = Bitboard::default();
-        for (dir_idx, dir) in dirs.iter().enumerate() {
-            let ray = self.rays[dir_idx][sq];
-
-            let pos: i32 = match dir {
-                &RayDirection::E | &RayDirection::N | &RayDirection::NE | &RayDirection::NW => {
-                    (ray & blockers).bitscan_reverse()
-                }
-                &RayDirection::W | &RayDirection::S | &RayDirection::SE | &RayDirection::SW => {
-                    (ray & blockers).bitscan_forward()
-                }
-            };
-
-            let block = Square::from_i32(pos).expect("Invalid square!");
-            let unreachable = self.rays[dir_idx][block];
+    /// Returns a [`Bitboard`] of the squares strictly between `from` and `to`
+    /// if they share a rank, file or diagonal, and an empty bitboard otherwise.
+    pub fn between(&self, from: Square, to: Square) -> Bitboard {
+        self.between[from as usize][to]
+    }
 
-            attacks |= ray & !unreachable;
-        }
-        attacks
+    /// Returns a [`Bitboard`] of the entire line (rank, file or diagonal) passing
+    /// through both `from` and `to`, edge to edge, or an empty bitboard if the
+    /// squares are not aligned.
+    pub fn line(&self, from: Square, to: Square) -> Bitboard {
+        self.line[from as usize][to]
     }
 }
 
@@ -167,7 +308,7 @@ impl Attacks {
 mod tests {
     use rstest::rstest;
 
-    use crate::{bitboard::Bitboard, square::Square};
+    use crate::{bitboard::Bitboard, color::Color, square::Square};
 
     use super::Attacks;
 
@@ -210,15 +351,156 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_pawn() {}
+    #[rstest]
+    #[case::white_center(Square::E4, Color::White, Bitboard::new(0x2800000000))]
+    #[case::white_h_file(Square::H2, Color::White, Bitboard::new(0x400000))]
+    #[case::black_a_file(Square::A8, Color::Black, Bitboard::new(0x2000000000000))]
+    #[case::black_center(Square::D4, Color::Black, Bitboard::new(0x140000))]
+    fn test_pawn(#[case] square: Square, #[case] color: Color, #[case] expected: Bitboard) {
+        let attacks = Attacks::new();
+        let actual = attacks.pawns(square, color);
+        assert_eq!(
+            expected, actual,
+            "expected\n{expected:?} but got\n{actual:?}"
+        );
+    }
 
     #[test]
-    fn test_bishop() {}
+    fn test_pawn_pushes() {
+        let attacks = Attacks::new();
+        let empty = !Bitboard::new(0x1100000);
 
-    #[test]
-    fn test_rook() {}
+        let pawns = Bitboard::new(0x80000000001100);
+        assert_eq!(
+            Bitboard::new(0x8000000000010000),
+            attacks.pawn_pushes(pawns, empty, Color::White)
+        );
+        assert_eq!(
+            Bitboard::default(),
+            attacks.pawn_double_pushes(pawns, empty, Color::White)
+        );
+
+        let pawns = Bitboard::new(0x300);
+        assert_eq!(
+            Bitboard::new(0x2000000),
+            attacks.pawn_double_pushes(pawns, empty, Color::White)
+        );
+
+        let pawns = pawns.flip_vertical();
+        let empty = empty.flip_vertical();
+        assert_eq!(
+            Bitboard::new(0x2000000).flip_vertical(),
+            attacks.pawn_double_pushes(pawns, empty, Color::Black)
+        );
+    }
+
+    #[rstest]
+    #[case::white(
+        Color::White,
+        Bitboard::new(0x8100),
+        Bitboard::new(0x20000),
+        Bitboard::new(0x400000)
+    )]
+    #[case::black(
+        Color::Black,
+        Bitboard::new(0x81000000000000),
+        Bitboard::new(0x20000000000),
+        Bitboard::new(0x400000000000)
+    )]
+    fn test_pawn_set_attacks(
+        #[case] color: Color,
+        #[case] pawns: Bitboard,
+        #[case] east: Bitboard,
+        #[case] west: Bitboard,
+    ) {
+        let attacks = Attacks::new();
+        assert_eq!(east, attacks.pawn_east_attacks(pawns, color));
+        assert_eq!(west, attacks.pawn_west_attacks(pawns, color));
+        assert_eq!(east | west, attacks.pawn_attacks(pawns, color));
+    }
 
     #[test]
-    fn test_queen() {}
+    fn test_pawn_en_passant() {
+        let attacks = Attacks::new();
+        let pawns = Bitboard::new(0x5400000000);
+        assert_eq!(
+            Bitboard::new(0x1400000000),
+            attacks.pawn_en_passant(pawns, Some(Square::D6), Color::White)
+        );
+        assert_eq!(
+            Bitboard::default(),
+            attacks.pawn_en_passant(pawns, None, Color::White)
+        );
+    }
+
+    #[rstest]
+    #[case::same_rank(Square::B1, Square::F1, Bitboard::new(0x1c), Bitboard::new(0xff))]
+    #[case::same_diagonal(
+        Square::H8,
+        Square::A1,
+        Bitboard::new(0x40201008040200),
+        Bitboard::new(0x8040201008040201)
+    )]
+    #[case::adjacent(
+        Square::E4,
+        Square::E5,
+        Bitboard::default(),
+        Bitboard::new(0x1010101010101010)
+    )]
+    #[case::unaligned(Square::A1, Square::B3, Bitboard::default(), Bitboard::default())]
+    fn test_between_and_line(
+        #[case] from: Square,
+        #[case] to: Square,
+        #[case] between: Bitboard,
+        #[case] line: Bitboard,
+    ) {
+        let attacks = Attacks::new();
+        assert_eq!(between, attacks.between(from, to));
+        assert_eq!(between, attacks.between(to, from));
+        assert_eq!(line, attacks.line(from, to));
+        assert_eq!(line, attacks.line(to, from));
+    }
+
+    #[rstest]
+    #[case::bishop_no_blockers(Square::D4, Bitboard::default(), Bitboard::new(0x8041221400142241))]
+    #[case::bishop_blocked(
+        Square::D4,
+        Bitboard::new(0x200000000200),
+        Bitboard::new(0x1221400142240)
+    )]
+    fn test_bishop(#[case] square: Square, #[case] blockers: Bitboard, #[case] expected: Bitboard) {
+        let attacks = Attacks::new();
+        let actual = attacks.bishop(square, blockers);
+        assert_eq!(
+            expected, actual,
+            "expected\n{expected:?} but got\n{actual:?}"
+        );
+    }
+
+    #[rstest]
+    #[case::rook_no_blockers(Square::A1, Bitboard::default(), Bitboard::new(0x1010101010101fe))]
+    #[case::rook_blocked(
+        Square::E4,
+        Bitboard::new(0x8000100004001000),
+        Bitboard::new(0x1010ec101000)
+    )]
+    fn test_rook(#[case] square: Square, #[case] blockers: Bitboard, #[case] expected: Bitboard) {
+        let attacks = Attacks::new();
+        let actual = attacks.rook(square, blockers);
+        assert_eq!(
+            expected, actual,
+            "expected\n{expected:?} but got\n{actual:?}"
+        );
+    }
+
+    #[rstest]
+    #[case::queen_starting_position(Square::D1, Bitboard::new(0xffff), Bitboard::new(0x1c14))]
+    fn test_queen(#[case] square: Square, #[case] blockers: Bitboard, #[case] expected: Bitboard) {
+        let attacks = Attacks::new();
+        let actual = attacks.queen(square, blockers);
+        assert_eq!(
+            expected, actual,
+            "expected\n{expected:?} but got\n{actual:?}"
+        );
+    }
 }
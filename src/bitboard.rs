@@ -6,10 +6,12 @@ use std::ops::{
 };
 
 use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive as _;
 use strum::IntoEnumIterator;
 
 use crate::board::{self, File, Rank};
 use crate::direction::Direction;
+use crate::square::Square;
 
 const DEBRUIJN_LOOKUP: [i32; 64] = [
     0, 47, 1, 56, 48, 27, 2, 60, 57, 49, 41, 37, 28, 16, 3, 61, 54, 58, 35, 52, 50, 42, 21, 44, 38,
@@ -64,6 +66,12 @@ impl BitAnd<u64> for Bitboard {
     }
 }
 
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
 impl BitAndAssign<usize> for Bitboard {
     fn bitand_assign(&mut self, rhs: usize) {
         self.0 &= rhs as u64;
@@ -102,6 +110,12 @@ impl BitXor for Bitboard {
     }
 }
 
+impl BitXorAssign for Bitboard {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0 ^= rhs.0;
+    }
+}
+
 impl BitXorAssign<usize> for Bitboard {
     fn bitxor_assign(&mut self, rhs: usize) {
         self.0 ^= rhs as u64;
@@ -167,7 +181,7 @@ impl ShlAssign<&Direction> for Bitboard {
         if shift > 0 {
             self.0 <<= shift;
         } else {
-            self.0 >>= shift;
+            self.0 >>= -shift;
         }
         self.0 &= mask;
     }
@@ -223,10 +237,15 @@ impl Display for Bitboard {
 
 impl Bitboard {
     /// Creates a new bitboard from the provided [`u64`].
-    pub fn new(data: u64) -> Bitboard {
+    pub const fn new(data: u64) -> Bitboard {
         Bitboard(data)
     }
 
+    /// Returns the raw [`u64`] backing this bitboard.
+    pub const fn value(&self) -> u64 {
+        self.0
+    }
+
     /// Returns the boolean representation of this bitboard.
     pub fn bool(&self) -> bool {
         self.0 != 0
@@ -256,7 +275,7 @@ impl Bitboard {
 
     /// Flip a bitboard vertically about the center ranks.
     /// Rank 1 is mapped to rank 8 and vice versa.
-    pub fn flip_vertical(&mut self) -> Bitboard {
+    pub fn flip_vertical(&self) -> Bitboard {
         let k1: u64 = 0x00FF00FF00FF00FF;
         let k2: u64 = 0x0000FFFF0000FFFF;
         let mut x: u64 = self.0;
@@ -319,6 +338,27 @@ impl Bitboard {
         DEBRUIJN_LOOKUP[key as usize]
     }
 
+    /// Returns the [`Square`] corresponding to the LS1B, or [`None`] if the
+    /// bitboard is empty.
+    pub fn lsb(&self) -> Option<Square> {
+        Square::from_i32(self.bitscan_forward())
+    }
+
+    /// Removes the LS1B from the bitboard and returns the [`Square`]
+    /// it corresponds to, or [`None`] if the bitboard is empty.
+    ///
+    /// This is the usual way to iterate over the squares in a set, e.g.
+    /// `while let Some(sq) = bb.pop_lsb() { ... }`.
+    pub fn pop_lsb(&mut self) -> Option<Square> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let sq = self.lsb();
+        self.0 &= self.0 - 1;
+        sq
+    }
+
     /// Convert the set-centric bitboard representation to
     /// a list of indices where bits are set.
     pub fn serialize(&self) -> Vec<usize> {
@@ -338,6 +378,7 @@ mod tests {
     use crate::{
         board,
         direction::{Direction, KnightDirection, RayDirection},
+        square::Square,
     };
 
     use super::Bitboard;
@@ -434,6 +475,16 @@ mod tests {
         assert_eq!(bb.bitscan_reverse(), first.max(second));
     }
 
+    #[test]
+    fn test_pop_lsb() {
+        let mut bb = Bitboard(0b1001 | (1 << 63));
+        assert_eq!(bb.pop_lsb(), Some(Square::A1));
+        assert_eq!(bb.pop_lsb(), Some(Square::D1));
+        assert_eq!(bb.pop_lsb(), Some(Square::H8));
+        assert_eq!(bb.pop_lsb(), None);
+        assert!(!bb.bool());
+    }
+
     #[test]
     fn test_serialize() {
         let mut sample_indices: Vec<usize> = rand::seq::index::sample(
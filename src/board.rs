@@ -80,6 +80,12 @@ pub const AB_FILE: u64 = 0x0303030303030303;
 pub const GH_FILE: u64 = 0xc0c0c0c0c0c0c0c0;
 pub const H_FILE: u64 = 0x8080808080808080;
 pub const FIRST_RANK: u64 = 0x00000000000000ff;
+pub const SECOND_RANK: u64 = 0x000000000000ff00;
+pub const THIRD_RANK: u64 = 0x0000000000ff0000;
+pub const FOURTH_RANK: u64 = 0x00000000ff000000;
+pub const FIFTH_RANK: u64 = 0x000000ff00000000;
+pub const SIXTH_RANK: u64 = 0x0000ff0000000000;
+pub const SEVENTH_RANK: u64 = 0x00ff000000000000;
 pub const EIGHTH_RANK: u64 = 0xff00000000000000;
 pub const A1_H8_DIAGONAL: u64 = 0x8040201008040201;
 pub const H1_A8_DIAGONAL: u64 = 0x0102040810204080;
@@ -2,7 +2,10 @@ use std::{fmt::Display, str::FromStr};
 
 use bitbybit::bitfield;
 
-use crate::error::DiogenesError;
+use crate::{
+    error::{DiogenesError, FenField},
+    square::Square,
+};
 
 /// A structure which encodes information about which side
 /// can castle in which direction.
@@ -32,6 +35,23 @@ pub struct CastlingRights {
     black_queenside: bool,
 }
 
+impl CastlingRights {
+    /// Returns these rights without any that are lost once a piece moves
+    /// from, or is captured on, `sq`, i.e. moving the king loses both rights
+    /// for that side and moving or losing a rook loses the right on its side.
+    pub fn revoke(&self, sq: Square) -> Self {
+        match sq {
+            Square::E1 => self.with_white_kingside(false).with_white_queenside(false),
+            Square::H1 => self.with_white_kingside(false),
+            Square::A1 => self.with_white_queenside(false),
+            Square::E8 => self.with_black_kingside(false).with_black_queenside(false),
+            Square::H8 => self.with_black_kingside(false),
+            Square::A8 => self.with_black_queenside(false),
+            _ => *self,
+        }
+    }
+}
+
 /// By default, we assume that all sides can castle anywhere.
 impl Default for CastlingRights {
     fn default() -> Self {
@@ -39,39 +59,51 @@ impl Default for CastlingRights {
     }
 }
 
+/// Parses the castling availability field of a FEN string.
+///
+/// The field must be either `-` or a non-empty subsequence of `KQkq`,
+/// so repeated or out-of-order characters such as `QQq` are rejected.
 impl FromStr for CastlingRights {
     type Err = DiogenesError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() > 4 {
-            return Err(DiogenesError::InvalidFenError {
-                fen: s.to_string(),
-                reason: format!(
-                    "castling rights field has length {} which exceeds max value of 4",
-                    s.len()
-                ),
+        if s == "-" {
+            return Ok(CastlingRights::ZERO);
+        }
+        if s.is_empty() {
+            return Err(DiogenesError::InvalidFenFieldError {
+                field: FenField::CastlingRights,
+                value: s.to_string(),
+                reason: "expected '-' or a combination of 'KQkq'".to_string(),
             });
         }
 
-        // TODO: This should handle bad FEN strings like QQq
-        match s {
-            "-" => Ok(CastlingRights::ZERO),
-            data => {
-                let cr = data
-                    .chars()
-                    .try_fold(CastlingRights::ZERO, |cr, ch| match ch {
-                        'K' => Ok(cr.with_white_kingside(true)),
-                        'Q' => Ok(cr.with_white_queenside(true)),
-                        'k' => Ok(cr.with_black_kingside(true)),
-                        'q' => Ok(cr.with_black_queenside(true)),
-                        c => Err(DiogenesError::InvalidFenError {
-                            fen: s.to_string(),
-                            reason: format!("invalid character {c} in castling rights FEN",),
-                        }),
-                    })?;
-                Ok(cr)
-            }
-        }
+        const ORDER: &str = "KQkq";
+        let mut next = 0;
+        s.chars()
+            .enumerate()
+            .try_fold(CastlingRights::ZERO, |cr, (index, ch)| {
+                let err = |reason: &str| DiogenesError::InvalidFenCharacterError {
+                    field: FenField::CastlingRights,
+                    index,
+                    ch,
+                    reason: reason.to_string(),
+                };
+                let pos = ORDER
+                    .find(ch)
+                    .ok_or_else(|| err("expected one of 'KQkq'"))?;
+                if pos < next {
+                    return Err(err("castling rights must be unique and ordered as 'KQkq'"));
+                }
+                next = pos + 1;
+
+                Ok(match ch {
+                    'K' => cr.with_white_kingside(true),
+                    'Q' => cr.with_white_queenside(true),
+                    'k' => cr.with_black_kingside(true),
+                    _ => cr.with_black_queenside(true),
+                })
+            })
     }
 }
 
@@ -82,10 +114,18 @@ impl Display for CastlingRights {
         }
 
         let mut s = String::with_capacity(4);
-        s.push_str(self.white_kingside().then_some("K").unwrap_or(""));
-        s.push_str(self.white_queenside().then_some("Q").unwrap_or(""));
-        s.push_str(self.black_kingside().then_some("k").unwrap_or(""));
-        s.push_str(self.black_queenside().then_some("q").unwrap_or(""));
+        if self.white_kingside() {
+            s.push('K');
+        }
+        if self.white_queenside() {
+            s.push('Q');
+        }
+        if self.black_kingside() {
+            s.push('k');
+        }
+        if self.black_queenside() {
+            s.push('q');
+        }
 
         write!(f, "{}", s)
     }
@@ -98,6 +138,8 @@ mod tests {
     use rstest::rstest;
 
     use crate::castling::CastlingRights;
+    use crate::error::{DiogenesError, FenField};
+    use crate::square::Square;
 
     #[rstest]
     #[case::all("KQkq", true, true, true, true)]
@@ -125,4 +167,42 @@ mod tests {
         assert_eq!(wk, cr.white_kingside());
         assert_eq!(wq, cr.white_queenside());
     }
+
+    #[rstest]
+    #[case::empty("", None)]
+    #[case::invalid_character("KX", Some((1, 'X')))]
+    #[case::duplicate("QQq", Some((1, 'Q')))]
+    #[case::out_of_order("kK", Some((1, 'K')))]
+    #[case::dash_with_rights("-K", Some((0, '-')))]
+    fn test_from_str_rejects(#[case] input: &str, #[case] at: Option<(usize, char)>) {
+        let err = CastlingRights::from_str(input).unwrap_err();
+        match (err, at) {
+            (
+                DiogenesError::InvalidFenCharacterError {
+                    field, index, ch, ..
+                },
+                Some(at),
+            ) => {
+                assert_eq!(FenField::CastlingRights, field);
+                assert_eq!(at, (index, ch));
+            }
+            (DiogenesError::InvalidFenFieldError { field, .. }, None) => {
+                assert_eq!(FenField::CastlingRights, field)
+            }
+            (err, _) => panic!("unexpected error {err:?}"),
+        }
+    }
+
+    #[rstest]
+    #[case::white_king(Square::E1, "kq")]
+    #[case::white_kingside_rook(Square::H1, "Qkq")]
+    #[case::white_queenside_rook(Square::A1, "Kkq")]
+    #[case::black_king(Square::E8, "KQ")]
+    #[case::black_kingside_rook(Square::H8, "KQq")]
+    #[case::black_queenside_rook(Square::A8, "KQk")]
+    #[case::unrelated_square(Square::E4, "KQkq")]
+    fn test_revoke(#[case] sq: Square, #[case] expected: &str) {
+        let cr = CastlingRights::default().revoke(sq);
+        assert_eq!(expected, cr.to_string());
+    }
 }
@@ -1,3 +1,5 @@
+use std::ops::Not;
+
 use crate::error::DiogenesError;
 
 #[derive(
@@ -27,3 +29,15 @@ pub enum Color {
 fn parse_err_fn(s: &str) -> DiogenesError {
     DiogenesError::InvalidColor(s.to_string())
 }
+
+impl Not for Color {
+    type Output = Color;
+
+    /// Returns the opposing [`Color`].
+    fn not(self) -> Self::Output {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
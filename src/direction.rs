@@ -1,3 +1,4 @@
+#![allow(clippy::upper_case_acronyms)]
 use num_derive::{FromPrimitive, ToPrimitive};
 
 use crate::board::{ALL_SQUARES, NOT_A_FILE, NOT_AB_FILE, NOT_GH_FILE, NOT_H_FILE};
@@ -88,6 +89,38 @@ pub enum RayDirection {
     NW = 7,
 }
 
+impl RayDirection {
+    /// The position of this direction in [`RayDirection::iter`] order, which
+    /// is used to index per-direction lookup tables.
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            RayDirection::N => 0,
+            RayDirection::NE => 1,
+            RayDirection::E => 2,
+            RayDirection::SE => 3,
+            RayDirection::S => 4,
+            RayDirection::SW => 5,
+            RayDirection::W => 6,
+            RayDirection::NW => 7,
+        }
+    }
+
+    /// Returns the direction pointing the opposite way, e.g. [`RayDirection::S`]
+    /// for [`RayDirection::N`].
+    pub(crate) fn opposite(&self) -> RayDirection {
+        match self {
+            RayDirection::N => RayDirection::S,
+            RayDirection::NE => RayDirection::SW,
+            RayDirection::E => RayDirection::W,
+            RayDirection::SE => RayDirection::NW,
+            RayDirection::S => RayDirection::N,
+            RayDirection::SW => RayDirection::NE,
+            RayDirection::W => RayDirection::E,
+            RayDirection::NW => RayDirection::SE,
+        }
+    }
+}
+
 #[derive(
     Debug,
     Clone,
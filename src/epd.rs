@@ -0,0 +1,493 @@
+//! Extended Position Description (EPD), the format of test suites such as
+//! Win At Chess (WAC) and the Strategic Test Suite (STS).
+//!
+//! An EPD record is made up of the first four fields of a FEN string and a
+//! list of operations, each an opcode followed by its operands and a
+//! semicolon, e.g. `bm Qxf7+; id "WAC.001";`.
+//! See <https://www.chessprogramming.org/Extended_Position_Description>
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use crate::error::{DiogenesError, DiogenesResult, FenField};
+use crate::r#move::Move;
+use crate::position::Position;
+use crate::search::{Iteration, Limits, Searcher, mate_in};
+use crate::tt::TranspositionTable;
+
+/// A position along with the operations describing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Epd {
+    position: Position,
+    operations: Vec<(String, Vec<String>)>,
+}
+
+fn invalid(operation: &str, reason: impl Into<String>) -> DiogenesError {
+    DiogenesError::InvalidEpdError {
+        operation: operation.to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// Returns `true` for the opcodes whose operand is free text, which is
+/// always quoted when written.
+fn is_string_opcode(opcode: &str) -> bool {
+    opcode == "id"
+        || opcode.len() == 2 && opcode.starts_with('c') && opcode.as_bytes()[1].is_ascii_digit()
+}
+
+impl Epd {
+    /// Returns the position described by the record. Its move counters come
+    /// from the `hmvc` and `fmvn` operations, if there are any.
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Returns every operation in the order they were written.
+    pub fn operations(&self) -> &[(String, Vec<String>)] {
+        &self.operations
+    }
+
+    /// Returns the operands of the operation called `opcode`, if any.
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|(op, _)| op == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+
+    /// Returns the single operand of the operation called `opcode`.
+    fn operand(&self, opcode: &str) -> Option<&str> {
+        match self.operation(opcode)? {
+            [operand] => Some(operand),
+            _ => None,
+        }
+    }
+
+    /// Returns the `id` of the position, which names it within its suite.
+    pub fn id(&self) -> Option<&str> {
+        self.operand("id")
+    }
+
+    /// Returns the primary comment `c0`.
+    pub fn comment(&self) -> Option<&str> {
+        self.operand("c0")
+    }
+
+    /// Returns the best moves `bm`, any of which solves the position.
+    pub fn best_moves(&self) -> Vec<Move> {
+        self.moves("bm").expect("moves are validated when parsed")
+    }
+
+    /// Returns the moves to avoid `am`, none of which solves the position.
+    pub fn avoid_moves(&self) -> Vec<Move> {
+        self.moves("am").expect("moves are validated when parsed")
+    }
+
+    /// Returns the number of moves `dm` in which the side to move mates.
+    pub fn direct_mate(&self) -> Option<i32> {
+        self.operand("dm")?.parse().ok()
+    }
+
+    /// Returns the analysis depth `acd`, in plies.
+    pub fn analysis_depth(&self) -> Option<u32> {
+        self.operand("acd")?.parse().ok()
+    }
+
+    /// Resolves the SAN operands of the operation called `opcode`.
+    fn moves(&self, opcode: &str) -> DiogenesResult<Vec<Move>> {
+        self.operation(opcode)
+            .unwrap_or_default()
+            .iter()
+            .map(|san| {
+                self.position
+                    .parse_san(san)
+                    .map_err(|error| invalid(opcode, error.to_string()))
+            })
+            .collect()
+    }
+
+    /// Returns `true` if `best`, found by a search scoring the position
+    /// `score`, answers it: it must be one of the best moves and none of the
+    /// moves to avoid, and the search must have found the direct mate.
+    /// A position without any of these operations can never be solved.
+    pub fn is_solved_by(&self, best: Option<Move>, score: i32) -> bool {
+        let best_moves = self.best_moves();
+        let avoid_moves = self.avoid_moves();
+        let direct_mate = self.direct_mate();
+        if best_moves.is_empty() && avoid_moves.is_empty() && direct_mate.is_none() {
+            return false;
+        }
+
+        best.is_some_and(|mv| {
+            (best_moves.is_empty() || best_moves.contains(&mv)) && !avoid_moves.contains(&mv)
+        }) && direct_mate.is_none_or(|dm| mate_in(score).is_some_and(|n| (1..=dm).contains(&n)))
+    }
+
+    /// Parses the operations following the four FEN fields of a record.
+    fn parse_operations(text: &str) -> DiogenesResult<Vec<(String, Vec<String>)>> {
+        let mut operations = Vec::new();
+        let mut chars = text.chars().peekable();
+
+        loop {
+            while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                return Ok(operations);
+            }
+
+            let mut opcode = String::new();
+            while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace() && *ch != ';') {
+                opcode.push(ch);
+            }
+            if !opcode.starts_with(|ch: char| ch.is_ascii_alphabetic())
+                || !opcode
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            {
+                return Err(invalid(&opcode, "expected an opcode"));
+            }
+
+            // The last semicolon of a line is often left out.
+            let mut operands = Vec::new();
+            loop {
+                while chars.next_if(|ch| ch.is_whitespace()).is_some() {}
+                match chars.next() {
+                    None | Some(';') => break,
+                    Some('"') => {
+                        let mut operand = String::new();
+                        loop {
+                            match chars.next() {
+                                Some('"') => break,
+                                Some(ch) => operand.push(ch),
+                                None => return Err(invalid(&opcode, "unterminated string")),
+                            }
+                        }
+                        operands.push(operand);
+                    }
+                    Some(ch) => {
+                        let mut operand = ch.to_string();
+                        while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace() && *ch != ';') {
+                            operand.push(ch);
+                        }
+                        operands.push(operand);
+                    }
+                }
+            }
+
+            operations.push((opcode, operands));
+        }
+    }
+}
+
+impl FromStr for Epd {
+    type Err = DiogenesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = Vec::with_capacity(4);
+        let mut rest = s.trim_start();
+        for field in [
+            FenField::Placement,
+            FenField::ActiveColor,
+            FenField::CastlingRights,
+            FenField::EnPassant,
+        ] {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(DiogenesError::MissingFenFieldError(field));
+            }
+            fields.push(&rest[..end]);
+            rest = rest[end..].trim_start();
+        }
+
+        let operations = Self::parse_operations(rest)?;
+        let counter = |opcode: &str, field: FenField, default: i32| {
+            let operands = operations
+                .iter()
+                .find(|(op, _)| op == opcode)
+                .map(|(_, operands)| operands.as_slice());
+            match operands {
+                None => Ok(default),
+                Some([value]) => Position::parse_counter(field, value),
+                Some(_) => Err(invalid(opcode, "expected a single operand")),
+            }
+        };
+        let halfmove = counter("hmvc", FenField::HalfmoveClock, 0)?;
+        let fullmove = counter("fmvn", FenField::FullmoveNumber, 1)?;
+
+        let position = Position::try_from_fields(
+            fields[0], fields[1], fields[2], fields[3], halfmove, fullmove,
+        )?;
+        let epd = Self {
+            position,
+            operations,
+        };
+        epd.moves("bm")?;
+        epd.moves("am")?;
+
+        Ok(epd)
+    }
+}
+
+impl Display for Epd {
+    /// Writes the record on a single line, with every operation terminated
+    /// by a semicolon.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fen = self.position.to_string();
+        let fields: Vec<&str> = fen.split(' ').take(4).collect();
+        write!(f, "{}", fields.join(" "))?;
+
+        for (opcode, operands) in &self.operations {
+            write!(f, " {opcode}")?;
+            for operand in operands {
+                if is_string_opcode(opcode)
+                    || operand.is_empty()
+                    || operand.contains(|ch: char| ch.is_whitespace() || ch == ';')
+                {
+                    write!(f, " \"{operand}\"")?;
+                } else {
+                    write!(f, " {operand}")?;
+                }
+            }
+            write!(f, ";")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The outcome of searching a single position of a test suite.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestResult {
+    /// The `id` of the position, or its index in the suite if it has none.
+    pub id: String,
+
+    /// The move the engine chose in SAN, if the position has a legal move.
+    pub played: Option<String>,
+
+    /// The answers the position asks for, e.g. `bm Qg6; am Kh1`.
+    pub expected: String,
+
+    /// The last iteration completed by the search.
+    pub iteration: Iteration,
+
+    pub solved: bool,
+}
+
+impl Display for TestResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} played {} expected {} depth {} score {}",
+            self.id,
+            if self.solved { "solved" } else { "FAILED" },
+            self.played.as_deref().unwrap_or("none"),
+            self.expected,
+            self.iteration.depth,
+            self.iteration.score
+        )
+    }
+}
+
+/// Searches every position of `suite` within `limits`, which should bound
+/// the search by depth, nodes or time, and passes each result to `report`
+/// as soon as it is known. The transposition table is cleared before each
+/// position so that the results do not depend on the order of the suite.
+pub fn run_suite(
+    suite: &[Epd],
+    limits: &Limits,
+    tt: Arc<TranspositionTable>,
+    mut report: impl FnMut(&TestResult),
+) -> Vec<TestResult> {
+    let mut results = Vec::with_capacity(suite.len());
+    for (index, epd) in suite.iter().enumerate() {
+        tt.clear();
+        let mut pos = epd.position().clone();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut searcher = Searcher::new(limits.clone(), stop, Arc::clone(&tt));
+        let iteration = searcher.search(&mut pos, |_| {});
+
+        let best = iteration.best_move();
+        let expected: Vec<String> = ["bm", "am", "dm"]
+            .into_iter()
+            .filter_map(|opcode| Some(format!("{opcode} {}", epd.operation(opcode)?.join(" "))))
+            .collect();
+        let result = TestResult {
+            id: epd
+                .id()
+                .map_or_else(|| (index + 1).to_string(), str::to_string),
+            played: best.map(|mv| epd.position().to_san(mv)),
+            expected: expected.join("; "),
+            solved: epd.is_solved_by(best, iteration.score),
+            iteration,
+        };
+        report(&result);
+        results.push(result);
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use rstest::rstest;
+
+    use crate::error::{DiogenesError, FenField};
+    use crate::search::{Limits, MATE};
+
+    use super::{Epd, run_suite};
+
+    const WAC_001: &str =
+        "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";";
+
+    fn epd(record: &str) -> Epd {
+        Epd::from_str(record).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let epd = epd(WAC_001);
+        assert_eq!(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1",
+            epd.position().to_string()
+        );
+        assert_eq!(Some("WAC.001"), epd.id());
+        assert_eq!(
+            vec!["g3g6"],
+            epd.best_moves()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+        assert!(epd.avoid_moves().is_empty());
+        assert_eq!(None, epd.comment());
+        assert_eq!(WAC_001, epd.to_string());
+    }
+
+    #[test]
+    fn test_parse_operations() {
+        let epd = epd(
+            "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - \
+             bm Nxc6 Qd2; am Nf5;c0 \"quiet; then sharp\"; acd 12; dm 3; hmvc 4; fmvn 7",
+        );
+        assert_eq!(
+            "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - 4 7",
+            epd.position().to_string()
+        );
+        assert_eq!(2, epd.best_moves().len());
+        assert_eq!(1, epd.avoid_moves().len());
+        assert_eq!(Some("quiet; then sharp"), epd.comment());
+        assert_eq!(Some(12), epd.analysis_depth());
+        assert_eq!(Some(3), epd.direct_mate());
+        assert_eq!(Some(&["4".to_string()][..]), epd.operation("hmvc"));
+        assert_eq!(None, epd.id());
+        assert_eq!(
+            "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - \
+             bm Nxc6 Qd2; am Nf5; c0 \"quiet; then sharp\"; acd 12; dm 3; hmvc 4; fmvn 7;",
+            epd.to_string()
+        );
+        assert_eq!(epd, Epd::from_str(&epd.to_string()).unwrap());
+    }
+
+    #[rstest]
+    #[case::missing_field(
+        "8/8/8/8/8/8/8/K6k w -",
+        DiogenesError::MissingFenFieldError(FenField::EnPassant)
+    )]
+    #[case::illegal_best_move(
+        "4k3/8/8/8/8/8/8/4K3 w - - bm Qh5;",
+        DiogenesError::InvalidEpdError {
+            operation: "bm".to_string(),
+            reason: "invalid SAN move \"Qh5\": no legal move matches".to_string(),
+        }
+    )]
+    #[case::unterminated_string(
+        "4k3/8/8/8/8/8/8/4K3 w - - id \"unterminated;",
+        DiogenesError::InvalidEpdError {
+            operation: "id".to_string(),
+            reason: "unterminated string".to_string(),
+        }
+    )]
+    #[case::bad_opcode(
+        "4k3/8/8/8/8/8/8/4K3 w - - 1bm Kd1;",
+        DiogenesError::InvalidEpdError {
+            operation: "1bm".to_string(),
+            reason: "expected an opcode".to_string(),
+        }
+    )]
+    #[case::bad_counter(
+        "4k3/8/8/8/8/8/8/4K3 w - - fmvn 0;",
+        DiogenesError::InvalidFenFieldError {
+            field: FenField::FullmoveNumber,
+            value: "0".to_string(),
+            reason: "the fullmove number starts at 1".to_string(),
+        }
+    )]
+    #[case::many_counters(
+        "4k3/8/8/8/8/8/8/4K3 w - - hmvc 1 2;",
+        DiogenesError::InvalidEpdError {
+            operation: "hmvc".to_string(),
+            reason: "expected a single operand".to_string(),
+        }
+    )]
+    fn test_parse_invalid(#[case] record: &str, #[case] expected: DiogenesError) {
+        assert_eq!(Err(expected), Epd::from_str(record));
+    }
+
+    #[rstest]
+    #[case::best_move("bm Ra8#;", "a1a8", 0, true)]
+    #[case::wrong_move("bm Ra8#;", "a1a7", 0, false)]
+    #[case::avoided_move("am Ra7;", "a1a7", 0, false)]
+    #[case::not_avoided_move("am Ra7;", "a1a8", 0, true)]
+    #[case::direct_mate("dm 1;", "a1a8", MATE - 1, true)]
+    #[case::mate_too_slow("dm 1;", "a1a8", MATE - 3, false)]
+    #[case::no_answers("id \"none\";", "a1a8", MATE - 1, false)]
+    fn test_is_solved_by(
+        #[case] operations: &str,
+        #[case] best: &str,
+        #[case] score: i32,
+        #[case] solved: bool,
+    ) {
+        let epd = epd(&format!("6k1/5ppp/8/8/8/8/8/R5K1 w - - {operations}"));
+        let best = epd.position().parse_uci_move(best).unwrap();
+        assert_eq!(solved, epd.is_solved_by(Some(best), score));
+    }
+
+    #[test]
+    fn test_run_suite() {
+        let suite = [
+            epd("6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; id \"mate\";"),
+            epd("4k3/8/4p3/3p4/8/8/8/3QK3 w - - am Qxd5;"),
+            // Any search finds the mate, so the suggested king move is wrong.
+            epd("6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Kf2;"),
+        ];
+        let limits = Limits {
+            depth: Some(3),
+            ..Limits::default()
+        };
+
+        let mut reported = Vec::new();
+        let results = run_suite(&suite, &limits, Arc::default(), |result| {
+            reported.push(result.clone())
+        });
+        assert_eq!(results, reported);
+        assert_eq!(
+            vec![true, true, false],
+            results.iter().map(|r| r.solved).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            format!(
+                "mate solved played Ra8# expected bm Ra8# depth {} score {}",
+                results[0].iteration.depth,
+                MATE - 1
+            ),
+            results[0].to_string()
+        );
+        assert_eq!("2", results[1].id);
+        assert_eq!("am Qxd5", results[1].expected);
+    }
+}
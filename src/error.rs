@@ -1,31 +1,74 @@
-use std::{fmt::Debug, num::ParseIntError};
+use std::fmt::Debug;
 
 use thiserror::Error;
 
+/// The six space-separated fields of a FEN string, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display, strum::EnumIter)]
+pub enum FenField {
+    #[strum(serialize = "piece placement")]
+    Placement,
+    #[strum(serialize = "active color")]
+    ActiveColor,
+    #[strum(serialize = "castling availability")]
+    CastlingRights,
+    #[strum(serialize = "en passant target square")]
+    EnPassant,
+    #[strum(serialize = "halfmove clock")]
+    HalfmoveClock,
+    #[strum(serialize = "fullmove number")]
+    FullmoveNumber,
+}
+
 /// TODO: Add better, more descriptive errors
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum DiogenesError {
     #[error("{0:?} is not a valid File")]
     InvalidFileError(String),
     #[error("{0:?} is not a valid Rank")]
     InvalidRankError(String),
-    #[error("{fen:?} is not a valid FEN string: {reason:?}")]
-    InvalidFenError { fen: String, reason: String },
+    #[error("FEN string is missing the {0} field")]
+    MissingFenFieldError(FenField),
+    #[error("FEN string has an unexpected trailing field {0:?}")]
+    TrailingFenFieldError(String),
+    #[error("invalid character {ch:?} at index {index} of the {field} field: {reason}")]
+    InvalidFenCharacterError {
+        field: FenField,
+        index: usize,
+        ch: char,
+        reason: String,
+    },
+    #[error("invalid {field} field {value:?}: {reason}")]
+    InvalidFenFieldError {
+        field: FenField,
+        value: String,
+        reason: String,
+    },
+    #[error("illegal position: {0}")]
+    IllegalPositionError(String),
     #[error("{0} is not a valid square")]
     InvalidSquareError(String),
     #[error("{0} is not a valid piece")]
     InvalidPieceError(String),
+    #[error("invalid move {text:?}: {reason}")]
+    InvalidMoveError { text: String, reason: String },
+    #[error("invalid SAN move {san:?}: {reason}")]
+    InvalidSanError { san: String, reason: String },
+    #[error("invalid EPD operation {operation:?}: {reason}")]
+    InvalidEpdError { operation: String, reason: String },
+    #[error("invalid PGN in game {game} at line {line}, column {column}: {reason}")]
+    InvalidPgnError {
+        game: usize,
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+    #[error("invalid UCI command: {0}")]
+    InvalidUciError(String),
+    #[error("I/O error: {0}")]
+    IoError(String),
     #[error("{0} does not represent a valid color, must be either 0 (White) or 1 (Black)")]
     InvalidColor(String),
 }
 
-impl From<ParseIntError> for DiogenesError {
-    fn from(err: ParseIntError) -> Self {
-        DiogenesError::InvalidFenError {
-            fen: String::from(""),
-            reason: err.to_string(),
-        }
-    }
-}
-
 pub type DiogenesResult<T> = anyhow::Result<T, DiogenesError>;
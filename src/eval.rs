@@ -0,0 +1,368 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::color::Color;
+use crate::pawns::PawnTable;
+use crate::piece::Piece;
+use crate::position::Position;
+use crate::square::Square;
+
+/// A pair of midgame and endgame scores, which are blended according to
+/// the phase of the game once the position is evaluated.
+///
+/// See <https://www.chessprogramming.org/Tapered_Eval>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Self { mg, eg }
+    }
+
+    /// Interpolates between the midgame and endgame scores, where `phase`
+    /// runs from [`MAX_PHASE`] with all pieces on the board down to zero
+    /// once only kings and pawns are left.
+    pub fn taper(self, phase: i32) -> i32 {
+        let phase = phase.clamp(0, MAX_PHASE);
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.mg + rhs.mg, self.eg + rhs.eg)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Score {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.mg - rhs.mg, self.eg - rhs.eg)
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self {
+        Self::new(self.mg * rhs, self.eg * rhs)
+    }
+}
+
+impl Neg for Score {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.mg, -self.eg)
+    }
+}
+
+/// The game phase with every non-pawn piece still on the board. Promotions
+/// can push the phase of a position above it, in which case it is capped.
+pub const MAX_PHASE: i32 = 24;
+
+/// Returns how much `piece` contributes to the game phase.
+pub fn phase(piece: Piece) -> i32 {
+    match piece {
+        Piece::WKnight | Piece::BKnight | Piece::WBishop | Piece::BBishop => 1,
+        Piece::WRook | Piece::BRook => 2,
+        Piece::WQueen | Piece::BQueen => 4,
+        Piece::WPawn | Piece::BPawn | Piece::WKing | Piece::BKing => 0,
+    }
+}
+
+/// Midgame and endgame material values, indexed by piece type. The king is
+/// never captured, so it carries no material value.
+const MATERIAL: [Score; 6] = [
+    Score::new(82, 94),
+    Score::new(337, 281),
+    Score::new(365, 297),
+    Score::new(477, 512),
+    Score::new(1025, 936),
+    Score::new(0, 0),
+];
+
+/// Piece-square tables from White's point of view, indexed by piece type.
+/// Each table is laid out as seen from White's side of the board, i.e. the
+/// first row is the eighth rank.
+///
+/// The values are those of PeSTO, tuned by Ronald Friederich.
+/// See <https://www.chessprogramming.org/PeSTO%27s_Evaluation_Function>
+#[rustfmt::skip]
+const MG_TABLES: [[i32; 64]; 6] = [
+    // Pawn
+    [
+          0,   0,   0,   0,   0,   0,  0,   0,
+         98, 134,  61,  95,  68, 126, 34, -11,
+         -6,   7,  26,  31,  65,  56, 25, -20,
+        -14,  13,   6,  21,  23,  12, 17, -23,
+        -27,  -2,  -5,  12,  17,   6, 10, -25,
+        -26,  -4,  -4, -10,   3,   3, 33, -12,
+        -35,  -1, -20, -23, -15,  24, 38, -22,
+          0,   0,   0,   0,   0,   0,  0,   0,
+    ],
+    // Knight
+    [
+        -167, -89, -34, -49,  61, -97, -15, -107,
+         -73, -41,  72,  36,  23,  62,   7,  -17,
+         -47,  60,  37,  65,  84, 129,  73,   44,
+          -9,  17,  19,  53,  37,  69,  18,   22,
+         -13,   4,  16,  13,  28,  19,  21,   -8,
+         -23,  -9,  12,  10,  19,  17,  25,  -16,
+         -29, -53, -12,  -3,  -1,  18, -14,  -19,
+        -105, -21, -58, -33, -17, -28, -19,  -23,
+    ],
+    // Bishop
+    [
+        -29,   4, -82, -37, -25, -42,   7,  -8,
+        -26,  16, -18, -13,  30,  59,  18, -47,
+        -16,  37,  43,  40,  35,  50,  37,  -2,
+         -4,   5,  19,  50,  37,  37,   7,  -2,
+         -6,  13,  13,  26,  34,  12,  10,   4,
+          0,  15,  15,  15,  14,  27,  18,  10,
+          4,  15,  16,   0,   7,  21,  33,   1,
+        -33,  -3, -14, -21, -13, -12, -39, -21,
+    ],
+    // Rook
+    [
+         32,  42,  32,  51, 63,  9,  31,  43,
+         27,  32,  58,  62, 80, 67,  26,  44,
+         -5,  19,  26,  36, 17, 45,  61,  16,
+        -24, -11,   7,  26, 24, 35,  -8, -20,
+        -36, -26, -12,  -1,  9, -7,   6, -23,
+        -45, -25, -16, -17,  3,  0,  -5, -33,
+        -44, -16, -20,  -9, -1, 11,  -6, -71,
+        -19, -13,   1,  17, 16,  7, -37, -26,
+    ],
+    // Queen
+    [
+        -28,   0,  29,  12,  59,  44,  43,  45,
+        -24, -39,  -5,   1, -16,  57,  28,  54,
+        -13, -17,   7,   8,  29,  56,  47,  57,
+        -27, -27, -16, -16,  -1,  17,  -2,   1,
+         -9, -26,  -9, -10,  -2,  -4,   3,  -3,
+        -14,   2, -11,  -2,  -5,   2,  14,   5,
+        -35,  -8,  11,   2,   8,  15,  -3,   1,
+         -1, -18,  -9,  10, -15, -25, -31, -50,
+    ],
+    // King
+    [
+        -65,  23,  16, -15, -56, -34,   2,  13,
+         29,  -1, -20,  -7,  -8,  -4, -38, -29,
+         -9,  24,   2, -16, -20,   6,  22, -22,
+        -17, -20, -12, -27, -30, -25, -14, -36,
+        -49,  -1, -27, -39, -46, -44, -33, -51,
+        -14, -14, -22, -46, -44, -30, -15, -27,
+          1,   7,  -8, -64, -43, -16,   9,   8,
+        -15,  36,  12, -54,   8, -28,  24,  14,
+    ],
+];
+
+#[rustfmt::skip]
+const EG_TABLES: [[i32; 64]; 6] = [
+    // Pawn
+    [
+          0,   0,   0,   0,   0,   0,   0,   0,
+        178, 173, 158, 134, 147, 132, 165, 187,
+         94, 100,  85,  67,  56,  53,  82,  84,
+         32,  24,  13,   5,  -2,   4,  17,  17,
+         13,   9,  -3,  -7,  -7,  -8,   3,  -1,
+          4,   7,  -6,   1,   0,  -5,  -1,  -8,
+         13,   8,   8,  10,  13,   0,   2,  -7,
+          0,   0,   0,   0,   0,   0,   0,   0,
+    ],
+    // Knight
+    [
+        -58, -38, -13, -28, -31, -27, -63, -99,
+        -25,  -8, -25,  -2,  -9, -25, -24, -52,
+        -24, -20,  10,   9,  -1,  -9, -19, -41,
+        -17,   3,  22,  22,  22,  11,   8, -18,
+        -18,  -6,  16,  25,  16,  17,   4, -18,
+        -23,  -3,  -1,  15,  10,  -3, -20, -22,
+        -42, -20, -10,  -5,  -2, -20, -23, -44,
+        -29, -51, -23, -15, -22, -18, -50, -64,
+    ],
+    // Bishop
+    [
+        -14, -21, -11,  -8, -7,  -9, -17, -24,
+         -8,  -4,   7, -12, -3, -13,  -4, -14,
+          2,  -8,   0,  -1, -2,   6,   0,   4,
+         -3,   9,  12,   9, 14,  10,   3,   2,
+         -6,   3,  13,  19,  7,  10,  -3,  -9,
+        -12,  -3,   8,  10, 13,   3,  -7, -15,
+        -14, -18,  -7,  -1,  4,  -9, -15, -27,
+        -23,  -9, -23,  -5, -9, -16,  -5, -17,
+    ],
+    // Rook
+    [
+        13, 10, 18, 15, 12,  12,   8,   5,
+        11, 13, 13, 11, -3,   3,   8,   3,
+         7,  7,  7,  5,  4,  -3,  -5,  -3,
+         4,  3, 13,  1,  2,   1,  -1,   2,
+         3,  5,  8,  4, -5,  -6,  -8, -11,
+        -4,  0, -5, -1, -7, -12,  -8, -16,
+        -6, -6,  0,  2, -9,  -9, -11,  -3,
+        -9,  2,  3, -1, -5, -13,   4, -20,
+    ],
+    // Queen
+    [
+         -9,  22,  22,  27,  27,  19,  10,  20,
+        -17,  20,  32,  41,  58,  25,  30,   0,
+        -20,   6,   9,  49,  47,  35,  19,   9,
+          3,  22,  24,  45,  57,  40,  57,  36,
+        -18,  28,  19,  47,  31,  34,  39,  23,
+        -16, -27,  15,   6,   9,  17,  10,   5,
+        -22, -23, -30, -16, -16, -23, -36, -32,
+        -33, -28, -22, -43,  -5, -32, -20, -41,
+    ],
+    // King
+    [
+        -74, -35, -18, -18, -11,  15,   4, -17,
+        -12,  17,  14,  17,  17,  38,  23,  11,
+         10,  17,  23,  15,  20,  45,  44,  13,
+         -8,  22,  24,  27,  26,  33,  26,   3,
+        -18,  -4,  21,  24,  27,  23,   9, -11,
+        -19,  -3,  11,  21,  23,  16,   7,  -9,
+        -27, -11,   4,  13,  14,   4,  -5, -17,
+        -53, -34, -21, -11, -28, -14, -24, -43,
+    ],
+];
+
+/// Returns the midgame and endgame material value of `piece`, regardless of
+/// its color.
+fn material(piece: Piece) -> Score {
+    MATERIAL[usize::from(piece) % 6]
+}
+
+/// Returns the material and piece-square value of `piece` standing on `sq`,
+/// from White's point of view. Black's tables mirror White's vertically.
+pub fn psqt(piece: Piece, sq: Square) -> Score {
+    let kind = usize::from(piece) % 6;
+    let index = match piece.color() {
+        Color::White => sq as usize ^ 56,
+        Color::Black => sq as usize,
+    };
+    let score = material(piece) + Score::new(MG_TABLES[kind][index], EG_TABLES[kind][index]);
+
+    match piece.color() {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+impl Position {
+    /// Statically evaluates the position in centipawns from the point of
+    /// view of the side to move, i.e. positive scores favour the side to move.
+    ///
+    /// Material and piece placement are kept up to date incrementally as
+    /// moves are made, see [`Position::psqt`] and [`Position::phase`], while
+    /// the pawn structure is looked up in `pawns`. King safety and mobility
+    /// are computed afresh, since they depend on every piece on the board.
+    pub fn evaluate(&self, pawns: &mut PawnTable) -> i32 {
+        let score = self.psqt() + pawns.probe(self) + self.king_safety() + self.mobility();
+        let white = score.taper(self.phase());
+
+        match self.side_to_move() {
+            Color::White => white,
+            Color::Black => -white,
+        }
+    }
+
+    /// Returns the material value of `piece` in centipawns, regardless of
+    /// its color, tapered to the phase of the position as in [`Self::evaluate`].
+    pub fn piece_value(&self, piece: Piece) -> i32 {
+        material(piece).taper(self.phase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use crate::pawns::PawnTable;
+    use crate::position::Position;
+
+    use super::{MAX_PHASE, Score};
+
+    fn evaluate(fen: &str) -> i32 {
+        Position::from_str(fen)
+            .unwrap()
+            .evaluate(&mut PawnTable::default())
+    }
+
+    #[rstest]
+    #[case::starting_position("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+    #[case::symmetric_middlegame(
+        "r1bqkb1r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R w KQkq - 4 4"
+    )]
+    #[case::bare_kings("4k3/8/8/8/8/8/8/4K3 b - - 0 1")]
+    fn test_symmetric_positions_are_equal(#[case] fen: &str) {
+        assert_eq!(0, evaluate(fen));
+    }
+
+    #[rstest]
+    #[case::knight("4k3/8/8/8/8/8/8/3NK3 w - - 0 1", "3nk3/8/8/8/8/8/8/4K3 b - - 0 1")]
+    #[case::kiwipete(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1"
+    )]
+    fn test_mirrored_positions_are_equal(#[case] fen: &str, #[case] mirrored: &str) {
+        assert_eq!(evaluate(fen), evaluate(mirrored));
+    }
+
+    #[rstest]
+    #[case::white_up_a_knight("4k3/8/8/8/8/8/8/3NK3 w - - 0 1", 1)]
+    #[case::black_to_move_down_a_knight("4k3/8/8/8/8/8/8/3NK3 b - - 0 1", -1)]
+    #[case::queen_against_rook("3rk3/8/8/8/8/8/8/3QK3 b - - 0 1", -1)]
+    fn test_material_advantage(#[case] fen: &str, #[case] sign: i32) {
+        let score = evaluate(fen);
+        assert_eq!(sign, score.signum(), "{score}");
+        assert!(score.abs() > 200, "{score}");
+    }
+
+    #[test]
+    fn test_central_knight_is_better() {
+        let center = evaluate("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        let corner = evaluate("4k3/8/8/8/8/8/8/N3K3 w - - 0 1");
+        assert!(center > corner);
+    }
+
+    #[rstest]
+    #[case::opening(MAX_PHASE, 100)]
+    #[case::endgame(0, 200)]
+    #[case::halfway(MAX_PHASE / 2, 150)]
+    #[case::extra_queens(MAX_PHASE + 8, 100)]
+    fn test_taper(#[case] phase: i32, #[case] expected: i32) {
+        assert_eq!(expected, Score::new(100, 200).taper(phase));
+    }
+
+    #[rstest]
+    #[case::starting_position(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        MAX_PHASE
+    )]
+    #[case::rooks("r3k3/pppp4/8/8/8/8/4PPPP/4K2R w - - 0 1", 4)]
+    #[case::pawns_only("4k3/pppp4/8/8/8/8/4PPPP/4K3 w - - 0 1", 0)]
+    fn test_phase(#[case] fen: &str, #[case] expected: i32) {
+        assert_eq!(expected, Position::from_str(fen).unwrap().phase());
+    }
+}
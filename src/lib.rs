@@ -1,13 +1,31 @@
 #![warn(unused_crate_dependencies)]
 #![allow(dead_code)]
-mod attacks;
-mod bitboard;
-mod board;
-mod castling;
-mod color;
-mod direction;
-mod error;
-mod r#move;
-mod piece;
-mod position;
-mod square;
+pub mod attacks;
+pub mod bitboard;
+pub mod board;
+pub mod castling;
+pub mod color;
+pub mod direction;
+pub mod epd;
+pub mod error;
+pub mod eval;
+mod magic;
+pub mod mobility;
+pub mod r#move;
+pub mod movegen;
+pub mod pawns;
+pub mod perft;
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+mod pext;
+pub mod pgn;
+pub mod piece;
+pub mod position;
+pub mod safety;
+pub mod san;
+pub mod search;
+pub mod see;
+pub mod square;
+pub mod timeman;
+pub mod tt;
+pub mod uci;
+pub mod zobrist;
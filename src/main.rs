@@ -1,3 +1,64 @@
-fn main() {
-    println!("Hello, world!");
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{env, fs, io};
+
+use diogenes::epd::{Epd, run_suite};
+use diogenes::search::Limits;
+use diogenes::timeman::TimeManager;
+use diogenes::tt::TranspositionTable;
+use diogenes::uci::Uci;
+
+/// The time spent on each position of a test suite unless told otherwise.
+const DEFAULT_SUITE_MOVETIME: Duration = Duration::from_secs(1);
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("epd") => run_epd(&args[1..]),
+        _ => Uci::new(io::stdout()).run(io::stdin().lock()),
+    }
+}
+
+/// Runs `diogenes epd <file> [depth <plies> | movetime <ms>]`, searching
+/// every position of a test suite and reporting which ones were solved.
+fn run_epd(args: &[String]) -> io::Result<()> {
+    let usage = || io::Error::other("usage: diogenes epd <file> [depth <plies> | movetime <ms>]");
+    let [path, rest @ ..] = args else {
+        return Err(usage());
+    };
+
+    let mut limits = Limits::default();
+    match rest {
+        [] => limits.time = Some(TimeManager::fixed(DEFAULT_SUITE_MOVETIME, Duration::ZERO)),
+        [limit, value] => match limit.as_str() {
+            "depth" => limits.depth = Some(value.parse().map_err(|_| usage())?),
+            "movetime" => {
+                let movetime = Duration::from_millis(value.parse().map_err(|_| usage())?);
+                limits.time = Some(TimeManager::fixed(movetime, Duration::ZERO));
+            }
+            _ => return Err(usage()),
+        },
+        _ => return Err(usage()),
+    }
+
+    // Malformed records are reported and skipped rather than aborting the
+    // whole suite.
+    let mut suite = Vec::new();
+    for (index, line) in fs::read_to_string(path)?.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match Epd::from_str(line) {
+            Ok(epd) => suite.push(epd),
+            Err(error) => eprintln!("{path}:{}: {error}", index + 1),
+        }
+    }
+
+    let tt = Arc::new(TranspositionTable::default());
+    let results = run_suite(&suite, &limits, tt, |result| println!("{result}"));
+    let solved = results.iter().filter(|result| result.solved).count();
+    println!("solved {solved} of {}", results.len());
+
+    Ok(())
 }
@@ -0,0 +1,173 @@
+//! Piece mobility evaluation.
+//!
+//! Pieces with more safe squares to move to are more useful, so each
+//! knight, bishop, rook and queen is scored by the number of squares it
+//! reaches that are neither occupied by its own pieces nor attacked by
+//! enemy pawns.
+//! See <https://www.chessprogramming.org/Mobility>
+
+use crate::attacks::ATTACKS;
+use crate::bitboard::Bitboard;
+use crate::color::Color;
+use crate::eval::Score;
+use crate::piece::Piece;
+use crate::position::Position;
+
+/// Mobility scores indexed by the number of safe squares a piece reaches.
+const KNIGHT_MOBILITY: [Score; 9] = [
+    Score::new(-31, -40),
+    Score::new(-26, -28),
+    Score::new(-6, -16),
+    Score::new(-2, -8),
+    Score::new(2, 2),
+    Score::new(6, 6),
+    Score::new(11, 8),
+    Score::new(14, 10),
+    Score::new(16, 12),
+];
+
+const BISHOP_MOBILITY: [Score; 14] = [
+    Score::new(-24, -30),
+    Score::new(-10, -12),
+    Score::new(8, -2),
+    Score::new(13, 6),
+    Score::new(19, 12),
+    Score::new(26, 21),
+    Score::new(28, 27),
+    Score::new(32, 28),
+    Score::new(32, 32),
+    Score::new(34, 36),
+    Score::new(40, 39),
+    Score::new(40, 43),
+    Score::new(46, 44),
+    Score::new(49, 48),
+];
+
+const ROOK_MOBILITY: [Score; 15] = [
+    Score::new(-30, -39),
+    Score::new(-10, -8),
+    Score::new(1, 12),
+    Score::new(2, 20),
+    Score::new(2, 35),
+    Score::new(6, 50),
+    Score::new(11, 52),
+    Score::new(16, 60),
+    Score::new(20, 67),
+    Score::new(20, 70),
+    Score::new(20, 79),
+    Score::new(24, 82),
+    Score::new(28, 84),
+    Score::new(28, 84),
+    Score::new(31, 86),
+];
+
+const QUEEN_MOBILITY: [Score; 28] = [
+    Score::new(-15, -24),
+    Score::new(-6, -15),
+    Score::new(-4, -4),
+    Score::new(-4, 10),
+    Score::new(10, 20),
+    Score::new(12, 28),
+    Score::new(12, 30),
+    Score::new(18, 38),
+    Score::new(19, 39),
+    Score::new(26, 48),
+    Score::new(32, 48),
+    Score::new(32, 50),
+    Score::new(32, 60),
+    Score::new(33, 64),
+    Score::new(34, 66),
+    Score::new(34, 66),
+    Score::new(36, 68),
+    Score::new(36, 70),
+    Score::new(38, 74),
+    Score::new(40, 75),
+    Score::new(46, 76),
+    Score::new(54, 84),
+    Score::new(54, 84),
+    Score::new(54, 86),
+    Score::new(55, 91),
+    Score::new(57, 91),
+    Score::new(57, 96),
+    Score::new(58, 110),
+];
+
+impl Position {
+    /// Evaluates the mobility of the pieces of both sides from White's
+    /// point of view.
+    pub fn mobility(&self) -> Score {
+        self.mobility_of(Color::White) - self.mobility_of(Color::Black)
+    }
+
+    /// Sums the mobility scores of every knight, bishop, rook and queen of
+    /// `color`.
+    fn mobility_of(&self, color: Color) -> Score {
+        let enemy_pawns = self.pieces()[Piece::WPawn.with_color(!color)];
+        let unsafe_squares = self.pieces()[color] | ATTACKS.pawn_attacks(enemy_pawns, !color);
+        let occupied = self.occupied();
+
+        let mut score = Score::default();
+        for kind in [Piece::WKnight, Piece::WBishop, Piece::WRook, Piece::WQueen] {
+            let mut squares = self.pieces()[kind.with_color(color)];
+            while let Some(sq) = squares.pop_lsb() {
+                let (attacks, table) = match kind {
+                    Piece::WKnight => (
+                        ATTACKS.knight(sq, Bitboard::default()),
+                        &KNIGHT_MOBILITY[..],
+                    ),
+                    Piece::WBishop => (ATTACKS.bishop(sq, occupied), &BISHOP_MOBILITY[..]),
+                    Piece::WRook => (ATTACKS.rook(sq, occupied), &ROOK_MOBILITY[..]),
+                    _ => (ATTACKS.queen(sq, occupied), &QUEEN_MOBILITY[..]),
+                };
+                score += table[(attacks & !unsafe_squares).popcount() as usize];
+            }
+        }
+
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use crate::eval::Score;
+    use crate::position::Position;
+
+    use super::{BISHOP_MOBILITY, KNIGHT_MOBILITY, QUEEN_MOBILITY, ROOK_MOBILITY};
+
+    fn mobility(fen: &str) -> Score {
+        Position::from_str(fen).unwrap().mobility()
+    }
+
+    #[rstest]
+    #[case::knight_in_center("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1", KNIGHT_MOBILITY[8])]
+    #[case::knight_in_corner("4k3/8/8/8/8/8/8/N3K3 w - - 0 1", KNIGHT_MOBILITY[2])]
+    #[case::own_pieces_block("4k3/8/8/8/8/1P6/2P5/N3K3 w - - 0 1", KNIGHT_MOBILITY[0])]
+    #[case::enemy_pawns_control("4k3/8/8/2p1p3/8/3N4/8/4K3 w - - 0 1", KNIGHT_MOBILITY[5])]
+    #[case::bishop_captures("4k3/8/8/8/8/2p5/1B6/4K3 w - - 0 1", BISHOP_MOBILITY[4])]
+    #[case::rook_on_open_board("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", ROOK_MOBILITY[10])]
+    #[case::black_queen("q3k3/8/8/8/8/8/8/4K3 w - - 0 1", -QUEEN_MOBILITY[17])]
+    fn test_mobility(#[case] fen: &str, #[case] expected: Score) {
+        assert_eq!(expected, mobility(fen));
+    }
+
+    #[rstest]
+    #[case::starting_position("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+    #[case::symmetric_middlegame(
+        "r1bqkb1r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQKB1R w KQkq - 4 4"
+    )]
+    fn test_symmetric_positions_are_equal(#[case] fen: &str) {
+        assert_eq!(Score::default(), mobility(fen));
+    }
+
+    #[test]
+    fn test_mirrored_positions_are_opposite() {
+        assert_eq!(
+            mobility("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"),
+            -mobility("r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1")
+        );
+    }
+}
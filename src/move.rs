@@ -1,3 +1,5 @@
+use std::fmt::Display;
+
 use arbitrary_int::{Number, u2, u4, u6};
 use bitbybit::bitfield;
 use num_traits::FromPrimitive;
@@ -74,10 +76,54 @@ pub struct Move {
 }
 
 impl Move {
+    /// Special move flags, i.e. the upper four bits of the lower half-word
+    /// as laid out in the table above.
+    pub const QUIET: u8 = 0b0000;
+    pub const DOUBLE_PAWN_PUSH: u8 = 0b0001;
+    pub const KINGSIDE_CASTLE: u8 = 0b0010;
+    pub const QUEENSIDE_CASTLE: u8 = 0b0011;
+    pub const CAPTURE: u8 = 0b0100;
+    pub const EN_PASSANT: u8 = 0b0101;
+    pub const KNIGHT_PROMOTION: u8 = 0b1000;
+    pub const BISHOP_PROMOTION: u8 = 0b1001;
+    pub const ROOK_PROMOTION: u8 = 0b1010;
+    pub const QUEEN_PROMOTION: u8 = 0b1011;
+    pub const KNIGHT_PROMOTION_CAPTURE: u8 = 0b1100;
+    pub const BISHOP_PROMOTION_CAPTURE: u8 = 0b1101;
+    pub const ROOK_PROMOTION_CAPTURE: u8 = 0b1110;
+    pub const QUEEN_PROMOTION_CAPTURE: u8 = 0b1111;
+
+    /// A sentinel "move" used to record a null move, i.e. passing the turn.
+    /// No real move has the same source and target square.
+    pub const NULL: Move = Move::ZERO;
+
     pub fn new(val: u32) -> Self {
         Self::new_with_raw_value(val)
     }
 
+    /// Encodes a move of `moved` from `from` to `to` with the given special
+    /// `flags`. `captured` is only recorded when the flags denote a capture.
+    pub fn encode(
+        from: Square,
+        to: Square,
+        flags: u8,
+        moved: Piece,
+        captured: Option<Piece>,
+    ) -> Self {
+        let captured = match captured {
+            Some(piece) if flags & Self::CAPTURE != 0 => piece as u32,
+            _ => 0,
+        };
+
+        Self::new(
+            (from as u32)
+                | (to as u32) << 6
+                | (flags as u32 & 0xF) << 12
+                | (moved as u32) << 16
+                | captured << 20,
+        )
+    }
+
     pub fn from(&self) -> Option<Square> {
         Square::from_u8(self.from_sq().as_u8())
     }
@@ -90,11 +136,143 @@ impl Move {
         Piece::from_u8(self.moved_piece().as_u8())
     }
 
+    /// Returns the captured piece, or [`None`] if this move is not a capture.
     pub fn captured(&self) -> Option<Piece> {
+        if !self.capture() {
+            return None;
+        }
         Piece::from_u8(self.captured_piece().as_u8())
     }
 
+    /// Returns the four special move bits, see [`Move`] for their meaning.
+    pub fn flags(&self) -> u8 {
+        (self.raw_value() >> 12) as u8 & 0xF
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Self::NULL
+    }
+
     pub fn is_quiet(&self) -> bool {
-        self.special().as_u8() == 0u8
+        self.flags() == Self::QUIET
+    }
+
+    pub fn is_double_pawn_push(&self) -> bool {
+        self.flags() == Self::DOUBLE_PAWN_PUSH
+    }
+
+    pub fn is_castle(&self) -> bool {
+        self.flags() == Self::KINGSIDE_CASTLE || self.flags() == Self::QUEENSIDE_CASTLE
+    }
+
+    pub fn is_en_passant(&self) -> bool {
+        self.flags() == Self::EN_PASSANT
+    }
+
+    /// Returns the piece a pawn is promoted to, in the color of the moving
+    /// side, or [`None`] if this move is not a promotion.
+    pub fn promoted(&self) -> Option<Piece> {
+        if !self.promotion() {
+            return None;
+        }
+
+        let piece = match self.special().as_u8() {
+            0 => Piece::WKnight,
+            1 => Piece::WBishop,
+            2 => Piece::WRook,
+            _ => Piece::WQueen,
+        };
+        Some(piece.with_color(self.moved()?.color()))
+    }
+}
+
+/// Formats the move in the coordinate notation used by UCI, e.g. `e2e4`,
+/// `e7e8q` or `e1g1` for castling. The null move is written as `0000`.
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.from(), self.to()) {
+            (Some(from), Some(to)) if !self.is_null() => {
+                write!(f, "{from}{to}")?;
+                if let Some(piece) = self.promoted() {
+                    write!(f, "{}", piece.to_string().to_lowercase())?;
+                }
+                Ok(())
+            }
+            _ => write!(f, "0000"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use crate::{piece::Piece, square::Square};
+
+    use super::Move;
+
+    #[test]
+    fn test_encode() {
+        let mv = Move::encode(
+            Square::E7,
+            Square::D8,
+            Move::QUEEN_PROMOTION_CAPTURE,
+            Piece::WPawn,
+            Some(Piece::BRook),
+        );
+        assert_eq!(mv.from(), Some(Square::E7));
+        assert_eq!(mv.to(), Some(Square::D8));
+        assert_eq!(mv.moved(), Some(Piece::WPawn));
+        assert_eq!(mv.captured(), Some(Piece::BRook));
+        assert_eq!(mv.promoted(), Some(Piece::WQueen));
+        assert!(mv.capture());
+        assert!(!mv.is_quiet());
+    }
+
+    #[rstest]
+    #[case::quiet(Move::QUIET, None, None)]
+    #[case::double_push(Move::DOUBLE_PAWN_PUSH, None, None)]
+    #[case::en_passant(Move::EN_PASSANT, Some(Piece::BPawn), None)]
+    #[case::knight_promotion(Move::KNIGHT_PROMOTION, None, Some(Piece::BKnight))]
+    #[case::bishop_promotion(Move::BISHOP_PROMOTION, None, Some(Piece::BBishop))]
+    #[case::rook_promotion(Move::ROOK_PROMOTION, None, Some(Piece::BRook))]
+    #[case::queen_promotion(Move::QUEEN_PROMOTION, None, Some(Piece::BQueen))]
+    fn test_flags(
+        #[case] flags: u8,
+        #[case] captured: Option<Piece>,
+        #[case] promoted: Option<Piece>,
+    ) {
+        let mv = Move::encode(Square::B2, Square::B1, flags, Piece::BPawn, captured);
+        assert_eq!(mv.flags(), flags);
+        assert_eq!(mv.captured(), captured);
+        assert_eq!(mv.promoted(), promoted);
+    }
+
+    #[rstest]
+    #[case::quiet(
+        Move::encode(Square::E2, Square::E4, Move::DOUBLE_PAWN_PUSH, Piece::WPawn, None),
+        "e2e4"
+    )]
+    #[case::castle(
+        Move::encode(Square::E1, Square::G1, Move::KINGSIDE_CASTLE, Piece::WKing, None),
+        "e1g1"
+    )]
+    #[case::promotion(
+        Move::encode(Square::E7, Square::E8, Move::QUEEN_PROMOTION, Piece::WPawn, None),
+        "e7e8q"
+    )]
+    #[case::black_underpromotion(
+        Move::encode(
+            Square::B2,
+            Square::A1,
+            Move::KNIGHT_PROMOTION_CAPTURE,
+            Piece::BPawn,
+            Some(Piece::WRook)
+        ),
+        "b2a1n"
+    )]
+    #[case::null(Move::NULL, "0000")]
+    fn test_display(#[case] mv: Move, #[case] expected: &str) {
+        assert_eq!(expected, mv.to_string());
     }
 }
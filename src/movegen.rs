@@ -0,0 +1,737 @@
+use std::str::FromStr;
+
+use num_traits::FromPrimitive;
+
+use crate::attacks::{ATTACKS, Attacks};
+use crate::bitboard::Bitboard;
+use crate::board::{EIGHTH_RANK, FIRST_RANK};
+use crate::color::Color;
+use crate::error::{DiogenesError, DiogenesResult};
+use crate::r#move::Move;
+use crate::piece::Piece;
+use crate::position::Position;
+use crate::square::Square;
+
+/// An upper bound on the number of moves in any reachable chess position,
+/// used to size move lists up front.
+pub const MAX_MOVES: usize = 256;
+
+const PROMOTIONS: [u8; 4] = [
+    Move::QUEEN_PROMOTION,
+    Move::ROOK_PROMOTION,
+    Move::BISHOP_PROMOTION,
+    Move::KNIGHT_PROMOTION,
+];
+
+impl Position {
+    /// Generates every pseudo-legal move for the side to move.
+    ///
+    /// Pseudo-legal moves obey the movement rules of each piece but may
+    /// leave the moving side's own king in check.
+    pub fn generate_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(MAX_MOVES);
+        let us = self.side_to_move();
+        let targets = !self.pieces()[us];
+
+        self.generate_pawn_moves(&mut moves);
+        for piece in [Piece::WKnight, Piece::WBishop, Piece::WRook, Piece::WQueen] {
+            self.generate_piece_moves(piece.with_color(us), targets, &mut moves);
+        }
+        self.generate_piece_moves(Piece::WKing.with_color(us), targets, &mut moves);
+        self.generate_castles(&mut moves);
+
+        moves
+    }
+
+    /// Generates every legal move for the side to move, i.e. every
+    /// pseudo-legal move that does not leave the moving side's king in check.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let restrictions = self.restrictions();
+        let mut moves = if restrictions.checkers.popcount() > 1 {
+            // In double check only the king can move.
+            let mut moves = Vec::with_capacity(8);
+            let king = Piece::WKing.with_color(self.side_to_move());
+            self.generate_piece_moves(king, !self.pieces()[self.side_to_move()], &mut moves);
+            moves
+        } else {
+            self.generate_moves()
+        };
+
+        moves.retain(|&mv| self.is_legal(mv, &restrictions));
+        moves
+    }
+
+    /// Generates the pseudo-legal captures and queen promotions for the side
+    /// to move, i.e. the moves that change the material balance the most.
+    ///
+    /// Underpromotions are left out as they are almost never better than
+    /// promoting to a queen.
+    pub fn generate_captures(&self) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(MAX_MOVES);
+        let us = self.side_to_move();
+        let enemies = self.pieces()[!us];
+
+        self.generate_pawn_promotions(&PROMOTIONS[..1], &mut moves);
+        self.generate_pawn_captures(&PROMOTIONS[..1], &mut moves);
+        for piece in [Piece::WKnight, Piece::WBishop, Piece::WRook, Piece::WQueen] {
+            self.generate_piece_moves(piece.with_color(us), enemies, &mut moves);
+        }
+        self.generate_piece_moves(Piece::WKing.with_color(us), enemies, &mut moves);
+
+        moves
+    }
+
+    /// Generates the legal subset of [`Self::generate_captures`].
+    pub fn legal_captures(&self) -> Vec<Move> {
+        let restrictions = self.restrictions();
+        let mut moves = if restrictions.checkers.popcount() > 1 {
+            let mut moves = Vec::with_capacity(8);
+            let king = Piece::WKing.with_color(self.side_to_move());
+            self.generate_piece_moves(king, self.pieces()[!self.side_to_move()], &mut moves);
+            moves
+        } else {
+            self.generate_captures()
+        };
+
+        moves.retain(|&mv| self.is_legal(mv, &restrictions));
+        moves
+    }
+
+    /// Resolves a move in the coordinate notation used by UCI, e.g. `e2e4`
+    /// or `e7e8q`, against the legal moves of the position. The result
+    /// carries the special flags and the moved and captured pieces, which
+    /// the text alone does not.
+    pub fn parse_uci_move(&self, text: &str) -> DiogenesResult<Move> {
+        let invalid = |reason: &str| DiogenesError::InvalidMoveError {
+            text: text.to_string(),
+            reason: reason.to_string(),
+        };
+
+        if !text.is_ascii() || !(4..=5).contains(&text.len()) {
+            return Err(invalid("expected a source and target square"));
+        }
+        let square = |s: &str| {
+            Square::from_str(s).map_err(|_| invalid("expected a source and target square"))
+        };
+        let from = square(&text[..2])?;
+        let to = square(&text[2..4])?;
+        let promotion = match text[4..].chars().next() {
+            None => None,
+            Some('n') => Some(Piece::WKnight),
+            Some('b') => Some(Piece::WBishop),
+            Some('r') => Some(Piece::WRook),
+            Some('q') => Some(Piece::WQueen),
+            Some(ch) => return Err(invalid(&format!("invalid promotion piece {ch:?}"))),
+        };
+
+        self.legal_moves()
+            .into_iter()
+            .find(|mv| {
+                mv.from() == Some(from)
+                    && mv.to() == Some(to)
+                    && mv.promoted().map(|piece| piece.with_color(Color::White)) == promotion
+            })
+            .ok_or_else(|| invalid("illegal move"))
+    }
+
+    /// Computes the checkers, pins and king danger squares that constrain
+    /// which pseudo-legal moves are legal for the side to move.
+    fn restrictions(&self) -> Restrictions {
+        let us = self.side_to_move();
+        let them = !us;
+        let occupied = self.occupied();
+        let king = self.king_square(us);
+
+        let checkers = self.checkers();
+        let check_mask = match checkers.popcount() {
+            0 => !Bitboard::default(),
+            _ => {
+                let checker = checkers.lsb().expect("checkers is not empty");
+                checkers | ATTACKS.between(king, checker)
+            }
+        };
+
+        // Enemy sliders which would attack the king if our own pieces were
+        // removed pin any single piece of ours standing in between.
+        let rooks = self.pieces()[Piece::WRook.with_color(them)]
+            | self.pieces()[Piece::WQueen.with_color(them)];
+        let bishops = self.pieces()[Piece::WBishop.with_color(them)]
+            | self.pieces()[Piece::WQueen.with_color(them)];
+        let mut snipers = (ATTACKS.rook(king, self.pieces()[them]) & rooks)
+            | (ATTACKS.bishop(king, self.pieces()[them]) & bishops);
+
+        let mut pinned = Bitboard::default();
+        while let Some(sniper) = snipers.pop_lsb() {
+            let blockers = ATTACKS.between(king, sniper) & occupied;
+            if blockers.popcount() == 1 {
+                pinned |= blockers & self.pieces()[us];
+            }
+        }
+
+        // The king must not be able to "hide" behind itself along a checking ray.
+        let danger = self.attacked_by(them, occupied ^ king.bitboard());
+
+        Restrictions {
+            king,
+            checkers,
+            check_mask,
+            pinned,
+            danger,
+        }
+    }
+
+    /// Returns `true` if the pseudo-legal `mv` does not leave the moving
+    /// side's king in check.
+    fn is_legal(&self, mv: Move, restrictions: &Restrictions) -> bool {
+        let from = mv.from().expect("move has a valid source square");
+        let to = mv.to().expect("move has a valid target square");
+
+        if mv.is_castle() {
+            // The king may not castle out of, through or into check.
+            let path = ATTACKS.between(from, to) | to.bitboard();
+            return !restrictions.checkers.bool() && !(path & restrictions.danger).bool();
+        }
+
+        if from == restrictions.king {
+            return !(to.bitboard() & restrictions.danger).bool();
+        }
+
+        if mv.is_en_passant() {
+            return self.is_legal_en_passant(from, to, restrictions.king);
+        }
+
+        if !(to.bitboard() & restrictions.check_mask).bool() {
+            return false;
+        }
+
+        !(from.bitboard() & restrictions.pinned).bool()
+            || (to.bitboard() & ATTACKS.line(restrictions.king, from)).bool()
+    }
+
+    /// En-passant captures remove two pieces from the same rank at once,
+    /// which can expose the king to a slider that no pin or check mask
+    /// accounts for, so they are verified by recomputing the king's attackers.
+    pub(crate) fn is_legal_en_passant(&self, from: Square, to: Square, king: Square) -> bool {
+        let captured = match self.side_to_move() {
+            Color::White => offset(to, -8),
+            Color::Black => offset(to, 8),
+        };
+        let occupied = self.occupied() ^ from.bitboard() ^ to.bitboard() ^ captured.bitboard();
+        let them = self.pieces()[!self.side_to_move()] ^ captured.bitboard();
+
+        !(self.attackers_to(king, occupied) & them).bool()
+    }
+
+    /// Returns a [`Bitboard`] of every square attacked by `color` given the
+    /// `occupied` squares.
+    fn attacked_by(&self, color: Color, occupied: Bitboard) -> Bitboard {
+        let pieces = self.pieces();
+        let mut attacked = ATTACKS.pawn_attacks(pieces[Piece::WPawn.with_color(color)], color);
+
+        let mut knights = pieces[Piece::WKnight.with_color(color)];
+        while let Some(sq) = knights.pop_lsb() {
+            attacked |= ATTACKS.knight(sq, Bitboard::default());
+        }
+
+        let queens = pieces[Piece::WQueen.with_color(color)];
+        let mut bishops = pieces[Piece::WBishop.with_color(color)] | queens;
+        while let Some(sq) = bishops.pop_lsb() {
+            attacked |= ATTACKS.bishop(sq, occupied);
+        }
+
+        let mut rooks = pieces[Piece::WRook.with_color(color)] | queens;
+        while let Some(sq) = rooks.pop_lsb() {
+            attacked |= ATTACKS.rook(sq, occupied);
+        }
+
+        let mut king = pieces[Piece::WKing.with_color(color)];
+        while let Some(sq) = king.pop_lsb() {
+            attacked |= ATTACKS.king(sq, Bitboard::default());
+        }
+
+        attacked
+    }
+
+    /// Generates pushes, double pushes, captures, en-passant captures and
+    /// promotions for every pawn of the side to move.
+    fn generate_pawn_moves(&self, moves: &mut Vec<Move>) {
+        self.generate_pawn_pushes(moves);
+        self.generate_pawn_promotions(&PROMOTIONS, moves);
+        self.generate_pawn_captures(&PROMOTIONS, moves);
+    }
+
+    /// Generates single and double pushes that do not promote.
+    fn generate_pawn_pushes(&self, moves: &mut Vec<Move>) {
+        let us = self.side_to_move();
+        let pawn = Piece::WPawn.with_color(us);
+        let pawns = self.pieces()[pawn];
+        let empty = self.empty();
+        let push = Attacks::pawn_push_direction(us) as i32;
+
+        let mut quiet = ATTACKS.pawn_pushes(pawns, empty, us) & !Self::last_rank(us);
+        while let Some(to) = quiet.pop_lsb() {
+            let from = offset(to, -push);
+            moves.push(Move::encode(from, to, Move::QUIET, pawn, None));
+        }
+
+        let mut double = ATTACKS.pawn_double_pushes(pawns, empty, us);
+        while let Some(to) = double.pop_lsb() {
+            let from = offset(to, -2 * push);
+            moves.push(Move::encode(from, to, Move::DOUBLE_PAWN_PUSH, pawn, None));
+        }
+    }
+
+    /// Generates pushes onto the last rank, promoting to each of `kinds`.
+    fn generate_pawn_promotions(&self, kinds: &[u8], moves: &mut Vec<Move>) {
+        let us = self.side_to_move();
+        let pawn = Piece::WPawn.with_color(us);
+        let push = Attacks::pawn_push_direction(us) as i32;
+
+        let mut promotions =
+            ATTACKS.pawn_pushes(self.pieces()[pawn], self.empty(), us) & Self::last_rank(us);
+        while let Some(to) = promotions.pop_lsb() {
+            let from = offset(to, -push);
+            for &flags in kinds {
+                moves.push(Move::encode(from, to, flags, pawn, None));
+            }
+        }
+    }
+
+    /// Generates captures and en-passant captures, where captures onto the
+    /// last rank promote to each of `kinds`.
+    fn generate_pawn_captures(&self, kinds: &[u8], moves: &mut Vec<Move>) {
+        let us = self.side_to_move();
+        let pawn = Piece::WPawn.with_color(us);
+        let pawns = self.pieces()[pawn];
+        let enemies = self.pieces()[!us];
+        let last_rank = Self::last_rank(us);
+
+        let captures = [
+            (
+                ATTACKS.pawn_west_attacks(pawns, us),
+                Attacks::pawn_west_direction(us),
+            ),
+            (
+                ATTACKS.pawn_east_attacks(pawns, us),
+                Attacks::pawn_east_direction(us),
+            ),
+        ];
+        for (attacks, dir) in captures {
+            let mut captures = attacks & enemies;
+            while let Some(to) = captures.pop_lsb() {
+                let from = offset(to, -(dir as i32));
+                let captured = self.piece(to);
+                if (to.bitboard() & last_rank).bool() {
+                    for &flags in kinds {
+                        moves.push(Move::encode(
+                            from,
+                            to,
+                            flags | Move::CAPTURE,
+                            pawn,
+                            captured,
+                        ));
+                    }
+                } else {
+                    moves.push(Move::encode(from, to, Move::CAPTURE, pawn, captured));
+                }
+            }
+        }
+
+        if let Some(ep) = self.ep() {
+            let captured = Piece::WPawn.with_color(!us);
+            let mut attackers = ATTACKS.pawn_en_passant(pawns, Some(ep), us);
+            while let Some(from) = attackers.pop_lsb() {
+                moves.push(Move::encode(
+                    from,
+                    ep,
+                    Move::EN_PASSANT,
+                    pawn,
+                    Some(captured),
+                ));
+            }
+        }
+    }
+
+    /// Returns the rank on which pawns of `color` promote.
+    fn last_rank(color: Color) -> Bitboard {
+        match color {
+            Color::White => Bitboard::new(EIGHTH_RANK),
+            Color::Black => Bitboard::new(FIRST_RANK),
+        }
+    }
+
+    /// Generates quiet moves and captures onto `targets` for every
+    /// non-pawn `piece` on the board.
+    fn generate_piece_moves(&self, piece: Piece, targets: Bitboard, moves: &mut Vec<Move>) {
+        let occupied = self.occupied();
+        let enemies = self.pieces()[!piece.color()];
+
+        let mut from_squares = self.pieces()[piece];
+        while let Some(from) = from_squares.pop_lsb() {
+            let attacks = match piece {
+                Piece::WKnight | Piece::BKnight => ATTACKS.knight(from, Bitboard::default()),
+                Piece::WBishop | Piece::BBishop => ATTACKS.bishop(from, occupied),
+                Piece::WRook | Piece::BRook => ATTACKS.rook(from, occupied),
+                Piece::WQueen | Piece::BQueen => ATTACKS.queen(from, occupied),
+                Piece::WKing | Piece::BKing => ATTACKS.king(from, Bitboard::default()),
+                Piece::WPawn | Piece::BPawn => unreachable!("pawns are generated set-wise"),
+            } & targets;
+
+            let mut captures = attacks & enemies;
+            while let Some(to) = captures.pop_lsb() {
+                moves.push(Move::encode(from, to, Move::CAPTURE, piece, self.piece(to)));
+            }
+
+            let mut quiets = attacks & !enemies;
+            while let Some(to) = quiets.pop_lsb() {
+                moves.push(Move::encode(from, to, Move::QUIET, piece, None));
+            }
+        }
+    }
+
+    /// Generates castling moves for which the side to move still holds the
+    /// right and the squares between king and rook are empty.
+    ///
+    /// Whether the king passes through an attacked square is not checked.
+    fn generate_castles(&self, moves: &mut Vec<Move>) {
+        let rights = self.castling_rights();
+        let occupied = self.occupied();
+
+        let (kingside, queenside, king, rank) = match self.side_to_move() {
+            Color::White => (
+                rights.white_kingside(),
+                rights.white_queenside(),
+                Piece::WKing,
+                0,
+            ),
+            Color::Black => (
+                rights.black_kingside(),
+                rights.black_queenside(),
+                Piece::BKing,
+                56,
+            ),
+        };
+
+        // Every square between the king and the rook must be empty.
+        let from = offset(Square::E1, rank);
+        let kingside_path = ATTACKS.between(from, offset(Square::H1, rank));
+        if kingside && !(occupied & kingside_path).bool() {
+            let to = offset(Square::G1, rank);
+            moves.push(Move::encode(from, to, Move::KINGSIDE_CASTLE, king, None));
+        }
+        let queenside_path = ATTACKS.between(from, offset(Square::A1, rank));
+        if queenside && !(occupied & queenside_path).bool() {
+            let to = offset(Square::C1, rank);
+            moves.push(Move::encode(from, to, Move::QUEENSIDE_CASTLE, king, None));
+        }
+    }
+}
+
+/// Constraints on the legal moves of the side to move.
+struct Restrictions {
+    /// The square of the side to move's king.
+    king: Square,
+
+    /// Enemy pieces currently giving check.
+    checkers: Bitboard,
+
+    /// Squares a non-king piece must move to in order to resolve a single
+    /// check, i.e. the checker and the squares between it and the king.
+    /// Every square when not in check.
+    check_mask: Bitboard,
+
+    /// Pieces of the side to move pinned against their own king.
+    pinned: Bitboard,
+
+    /// Squares attacked by the opponent, on which the king may not stand.
+    danger: Bitboard,
+}
+
+/// Returns the square `delta` indices away from `sq`.
+///
+/// Callers must guarantee that the resulting square is on the board.
+fn offset(sq: Square, delta: i32) -> Square {
+    Square::from_i32(sq as i32 + delta).expect("square offset left the board")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use crate::color::Color;
+    use crate::error::DiogenesError;
+    use crate::r#move::Move;
+    use crate::piece::Piece;
+    use crate::position::Position;
+    use crate::square::Square;
+
+    #[rstest]
+    #[case::starting_position("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 20)]
+    #[case::kiwipete(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        48
+    )]
+    #[case::black_to_move("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 20)]
+    #[case::promotion("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", 9)]
+    #[case::en_passant("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", 7)]
+    fn test_generate_moves_count(#[case] fen: &str, #[case] expected: usize) {
+        let pos = Position::from_str(fen).unwrap();
+        let moves = pos.generate_moves();
+        assert_eq!(expected, moves.len(), "got {moves:#?}");
+    }
+
+    #[test]
+    fn test_generate_special_moves() {
+        let pos = Position::from_str("r3k2r/1P6/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        let moves = pos.generate_moves();
+
+        let expected = [
+            Move::encode(
+                Square::E1,
+                Square::G1,
+                Move::KINGSIDE_CASTLE,
+                Piece::WKing,
+                None,
+            ),
+            Move::encode(
+                Square::E1,
+                Square::C1,
+                Move::QUEENSIDE_CASTLE,
+                Piece::WKing,
+                None,
+            ),
+            Move::encode(
+                Square::E5,
+                Square::D6,
+                Move::EN_PASSANT,
+                Piece::WPawn,
+                Some(Piece::BPawn),
+            ),
+            Move::encode(
+                Square::B7,
+                Square::B8,
+                Move::KNIGHT_PROMOTION,
+                Piece::WPawn,
+                None,
+            ),
+            Move::encode(
+                Square::B7,
+                Square::A8,
+                Move::QUEEN_PROMOTION_CAPTURE,
+                Piece::WPawn,
+                Some(Piece::BRook),
+            ),
+            Move::encode(
+                Square::A1,
+                Square::A8,
+                Move::CAPTURE,
+                Piece::WRook,
+                Some(Piece::BRook),
+            ),
+        ];
+        for mv in expected {
+            assert!(moves.contains(&mv), "expected {mv:?} in {moves:#?}");
+        }
+    }
+
+    #[rstest]
+    #[case::starting_position("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 20)]
+    #[case::kiwipete(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        48
+    )]
+    #[case::pinned_rook("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 14)]
+    #[case::in_check("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 6)]
+    #[case::discovered_promotions("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 44)]
+    #[case::middlegame(
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        46
+    )]
+    #[case::double_check("4k3/8/8/8/8/5n2/8/4K2r w - - 0 1", 2)]
+    #[case::en_passant_discovered_check("8/8/8/KPp4r/8/8/8/7k w - c6 0 1", 4)]
+    #[case::castle_through_check("4k3/8/8/8/8/8/5r2/R3K2R w KQ - 0 1", 22)]
+    fn test_legal_moves_count(#[case] fen: &str, #[case] expected: usize) {
+        let pos = Position::from_str(fen).unwrap();
+        let moves = pos.legal_moves();
+        assert_eq!(expected, moves.len(), "got {moves:#?}");
+    }
+
+    #[rstest]
+    #[case::starting_position("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0)]
+    #[case::kiwipete(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        8
+    )]
+    #[case::promotions("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1", 6)]
+    #[case::en_passant("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", 1)]
+    #[case::en_passant_discovered_check("8/8/8/KPp4r/8/8/8/7k w - c6 0 1", 0)]
+    #[case::double_check("4k3/8/8/8/8/5n2/8/4K2r w - - 0 1", 0)]
+    fn test_legal_captures(#[case] fen: &str, #[case] expected: usize) {
+        let pos = Position::from_str(fen).unwrap();
+        let captures = pos.legal_captures();
+        let mut oracle: Vec<Move> = pos
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| match mv.promoted() {
+                Some(piece) => piece.with_color(Color::White) == Piece::WQueen,
+                None => mv.captured().is_some(),
+            })
+            .collect();
+
+        assert_eq!(expected, captures.len(), "got {captures:#?}");
+        let mut sorted = captures.clone();
+        sorted.sort();
+        oracle.sort();
+        assert_eq!(oracle, sorted);
+    }
+
+    #[test]
+    fn test_legal_moves_en_passant_discovered_check() {
+        let pos = Position::from_str("8/8/8/KPp4r/8/8/8/7k w - c6 0 1").unwrap();
+        let ep = Move::encode(
+            Square::B5,
+            Square::C6,
+            Move::EN_PASSANT,
+            Piece::WPawn,
+            Some(Piece::BPawn),
+        );
+        assert!(pos.generate_moves().contains(&ep));
+        assert!(!pos.legal_moves().contains(&ep));
+    }
+
+    #[test]
+    fn test_legal_moves_castle_through_check() {
+        let pos = Position::from_str("4k3/8/8/8/8/8/5r2/R3K2R w KQ - 0 1").unwrap();
+        let kingside = Move::encode(
+            Square::E1,
+            Square::G1,
+            Move::KINGSIDE_CASTLE,
+            Piece::WKing,
+            None,
+        );
+        let queenside = Move::encode(
+            Square::E1,
+            Square::C1,
+            Move::QUEENSIDE_CASTLE,
+            Piece::WKing,
+            None,
+        );
+        assert!(!pos.legal_moves().contains(&kingside));
+        assert!(pos.legal_moves().contains(&queenside));
+    }
+
+    #[rstest]
+    #[case::white("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &["e1g1", "e1c1"])]
+    #[case::black("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", &["e8g8", "e8c8"])]
+    #[case::white_blocked("r3k2r/8/8/8/8/8/8/RN2K1NR w KQkq - 0 1", &[])]
+    #[case::black_blocked("rn2k1nr/8/8/8/8/8/8/R3K2R b KQkq - 0 1", &[])]
+    #[case::only_rook_path_blocked("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1", &["e1g1"])]
+    fn test_castles_need_empty_path(#[case] fen: &str, #[case] expected: &[&str]) {
+        let pos = Position::from_str(fen).unwrap();
+        let mut castles: Vec<String> = pos
+            .legal_moves()
+            .into_iter()
+            .filter(Move::is_castle)
+            .map(|mv| mv.to_string())
+            .collect();
+        castles.sort();
+        let mut expected = expected.to_vec();
+        expected.sort();
+        assert_eq!(expected, castles);
+    }
+
+    /// Checks [`Position::legal_moves`] against the naive approach of making
+    /// each pseudo-legal move and checking whether the king can be captured.
+    #[rstest]
+    #[case::kiwipete("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+    #[case::pinned_rook("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1")]
+    #[case::in_check("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")]
+    #[case::discovered_promotions("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8")]
+    fn test_legal_moves_match_make_and_test(#[case] fen: &str) {
+        fn king_capturable(pos: &Position) -> bool {
+            let king = pos.pieces()[Piece::WKing.with_color(!pos.side_to_move())];
+            pos.generate_moves()
+                .iter()
+                .any(|mv| (mv.to().unwrap().bitboard() & king).bool())
+        }
+
+        let mut pos = Position::from_str(fen).unwrap();
+        for mv in pos.clone().generate_moves() {
+            pos.make_move(mv);
+            for reply in pos.clone().generate_moves() {
+                if reply.is_castle() {
+                    continue;
+                }
+                pos.make_move(reply);
+                let legal = !king_capturable(&pos);
+                pos.unmake_move();
+                assert_eq!(
+                    legal,
+                    pos.legal_moves().contains(&reply),
+                    "{reply:?} in {pos:?}"
+                );
+            }
+            pos.unmake_move();
+        }
+    }
+
+    #[rstest]
+    #[case::double_push(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "e2e4",
+        Move::encode(Square::E2, Square::E4, Move::DOUBLE_PAWN_PUSH, Piece::WPawn, None)
+    )]
+    #[case::castle(
+        "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+        "e8c8",
+        Move::encode(Square::E8, Square::C8, Move::QUEENSIDE_CASTLE, Piece::BKing, None)
+    )]
+    #[case::en_passant(
+        "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        "e5d6",
+        Move::encode(
+            Square::E5,
+            Square::D6,
+            Move::EN_PASSANT,
+            Piece::WPawn,
+            Some(Piece::BPawn)
+        )
+    )]
+    #[case::promotion_capture(
+        "1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+        "a7b8r",
+        Move::encode(
+            Square::A7,
+            Square::B8,
+            Move::ROOK_PROMOTION_CAPTURE,
+            Piece::WPawn,
+            Some(Piece::BRook)
+        )
+    )]
+    fn test_parse_uci_move(#[case] fen: &str, #[case] text: &str, #[case] expected: Move) {
+        let pos = Position::from_str(fen).unwrap();
+        assert_eq!(Ok(expected), pos.parse_uci_move(text));
+        assert_eq!(text, expected.to_string());
+    }
+
+    #[rstest]
+    #[case::too_short("e2", "expected a source and target square")]
+    #[case::bad_square("e2e9", "expected a source and target square")]
+    #[case::non_ascii("e2e4\u{e9}", "expected a source and target square")]
+    #[case::bad_promotion("a7a8k", "invalid promotion piece 'k'")]
+    #[case::missing_promotion("a7a8", "illegal move")]
+    #[case::illegal("e1e3", "illegal move")]
+    #[case::null("0000", "expected a source and target square")]
+    fn test_parse_invalid_uci_move(#[case] text: &str, #[case] reason: &str) {
+        let pos = Position::from_str("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            Err(DiogenesError::InvalidMoveError {
+                text: text.to_string(),
+                reason: reason.to_string(),
+            }),
+            pos.parse_uci_move(text)
+        );
+    }
+}
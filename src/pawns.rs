@@ -0,0 +1,232 @@
+//! Pawn structure evaluation.
+//!
+//! Pawns move slowly and never backwards, so very many of the positions in
+//! a search tree share the same pawn skeleton. The analysis is therefore
+//! cached in a [`PawnTable`] keyed by [`Position::pawn_hash`].
+//! See <https://www.chessprogramming.org/Pawn_Structure>
+
+use crate::bitboard::Bitboard;
+use crate::direction::{Direction, RayDirection};
+use crate::eval::Score;
+use crate::piece::Piece;
+use crate::position::Position;
+
+/// The number of entries in a [`PawnTable`], which must be a power of two.
+const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+/// Penalty for each pawn standing behind a friendly pawn on the same file.
+const DOUBLED: Score = Score::new(-10, -25);
+
+/// Penalty for each pawn without friendly pawns on the adjacent files.
+const ISOLATED: Score = Score::new(-10, -15);
+
+/// Penalty for each pawn that can no longer be supported by friendly pawns
+/// and whose advance is controlled by an enemy pawn.
+const BACKWARD: Score = Score::new(-8, -10);
+
+/// Bonus for each pawn defended by or standing beside a friendly pawn.
+const CONNECTED: Score = Score::new(8, 6);
+
+/// Bonus for each passed pawn, indexed by its rank relative to its side.
+const PASSED: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(5, 10),
+    Score::new(5, 15),
+    Score::new(10, 25),
+    Score::new(20, 45),
+    Score::new(40, 80),
+    Score::new(70, 130),
+    Score::new(0, 0),
+];
+
+const NORTH: Direction = Direction::Ray(RayDirection::N);
+const SOUTH: Direction = Direction::Ray(RayDirection::S);
+const EAST: Direction = Direction::Ray(RayDirection::E);
+const WEST: Direction = Direction::Ray(RayDirection::W);
+
+/// Returns the squares on the files adjacent to the squares in `bb`.
+pub(crate) fn adjacent(bb: Bitboard) -> Bitboard {
+    (bb << &EAST) | (bb << &WEST)
+}
+
+/// Returns the squares strictly behind the squares in `bb`, looking from
+/// White's side of the board.
+fn rear_span(bb: Bitboard) -> Bitboard {
+    (bb << &SOUTH).fill_all(&SOUTH)
+}
+
+/// Returns every square on the files of the squares in `bb`.
+pub(crate) fn file_fill(bb: Bitboard) -> Bitboard {
+    bb.fill_all(&NORTH) | bb.fill_all(&SOUTH)
+}
+
+/// Evaluates the pawns `ours` against the pawns `theirs`, with `ours`
+/// moving up the board as White's pawns do. Black's pawns are evaluated by
+/// flipping both sets vertically first.
+fn evaluate_side(ours: Bitboard, theirs: Bitboard) -> Score {
+    let mut score = Score::default();
+
+    let doubled = ours & rear_span(ours);
+    score += DOUBLED * doubled.popcount();
+
+    let isolated = ours & !adjacent(file_fill(ours));
+    score += ISOLATED * isolated.popcount();
+
+    // A pawn can only be supported by pawns on the adjacent files that are
+    // level with it or further back, since pawns never retreat.
+    let supportable = adjacent(ours).fill_all(&NORTH);
+    let their_attacks = adjacent(theirs << &SOUTH);
+    let backward = ours & !supportable & !isolated & ((their_attacks & !ours) << &SOUTH);
+    score += BACKWARD * backward.popcount();
+
+    let supported = ours & adjacent(ours << &NORTH);
+    let phalanx = ours & adjacent(ours);
+    score += CONNECTED * (supported | phalanx).popcount();
+
+    let blocked = {
+        let span = rear_span(theirs);
+        span | adjacent(span)
+    };
+    let mut passed = ours & !blocked & !rear_span(ours);
+    while let Some(sq) = passed.pop_lsb() {
+        score += PASSED[sq as usize / 8];
+    }
+
+    score
+}
+
+impl Position {
+    /// Evaluates the pawn structure of the position from White's point of
+    /// view, rewarding connected and passed pawns and penalising doubled,
+    /// isolated and backward ones.
+    pub fn pawn_structure(&self) -> Score {
+        let white = self.pieces()[Piece::WPawn];
+        let black = self.pieces()[Piece::BPawn];
+
+        evaluate_side(white, black) - evaluate_side(black.flip_vertical(), white.flip_vertical())
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct PawnEntry {
+    key: u64,
+    score: Score,
+}
+
+/// A cache of [`Position::pawn_structure`] results, keyed by
+/// [`Position::pawn_hash`]. Each search thread owns its own table.
+///
+/// Empty entries have a key of zero, which is also the key of a position
+/// without pawns. Their pawn structure scores zero, so such a hit is correct.
+pub struct PawnTable {
+    entries: Vec<PawnEntry>,
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        Self {
+            entries: vec![PawnEntry::default(); PAWN_TABLE_SIZE],
+        }
+    }
+}
+
+impl PawnTable {
+    /// Returns the pawn structure score of `pos`, analysing its pawns only
+    /// if the table holds no result for the same pawn skeleton.
+    pub fn probe(&mut self, pos: &Position) -> Score {
+        let key = pos.pawn_hash();
+        let entry = &mut self.entries[key as usize & (PAWN_TABLE_SIZE - 1)];
+        if entry.key != key {
+            *entry = PawnEntry {
+                key,
+                score: pos.pawn_structure(),
+            };
+        }
+
+        entry.score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use crate::eval::Score;
+    use crate::piece::Piece;
+    use crate::position::Position;
+
+    use super::{BACKWARD, CONNECTED, DOUBLED, ISOLATED, PASSED, PawnTable};
+
+    #[rstest]
+    #[case::no_pawns("4k3/8/8/8/8/8/8/4K3 w - - 0 1", Score::default())]
+    #[case::starting_position(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        Score::default()
+    )]
+    #[case::isolated_pawns("4k3/pp6/8/8/8/8/P1P5/4K3 w - - 0 1", ISOLATED * 2 - CONNECTED * 2)]
+    #[case::doubled_pawns(
+        "4k3/1pp5/8/8/8/2P5/2P5/4K3 w - - 0 1",
+        DOUBLED + ISOLATED * 2 - CONNECTED * 2
+    )]
+    #[case::passed_pawn("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1", ISOLATED + PASSED[4])]
+    #[case::blocked_by_adjacent_file("4k3/4p3/8/3P4/8/8/8/4K3 w - - 0 1", Score::default())]
+    #[case::connected_passers(
+        "4k3/8/3PP3/8/8/8/8/4K3 w - - 0 1",
+        CONNECTED * 2 + PASSED[5] * 2
+    )]
+    #[case::backward_pawn(
+        "4k3/8/8/2p5/P7/1P6/8/4K3 w - - 0 1",
+        CONNECTED + BACKWARD + PASSED[3] - ISOLATED
+    )]
+    fn test_pawn_structure(#[case] fen: &str, #[case] expected: Score) {
+        let pos = Position::from_str(fen).unwrap();
+        assert_eq!(expected, pos.pawn_structure());
+    }
+
+    #[rstest]
+    #[case::kiwipete(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1"
+    )]
+    #[case::passers("4k3/8/3PP3/8/8/8/8/4K3 w - - 0 1", "4k3/8/8/8/8/3pp3/8/4K3 b - - 0 1")]
+    fn test_mirrored_pawn_structure(#[case] fen: &str, #[case] mirrored: &str) {
+        let pos = Position::from_str(fen).unwrap();
+        let mirrored = Position::from_str(mirrored).unwrap();
+        assert_eq!(pos.pawn_structure(), -mirrored.pawn_structure());
+    }
+
+    #[test]
+    fn test_pawn_hash() {
+        let mut pos = Position::from_str("4k3/8/8/8/8/8/4P3/4K1N1 w - - 0 1").unwrap();
+        let original = pos.pawn_hash();
+
+        // Piece moves leave the pawn skeleton untouched.
+        for mv in pos.clone().legal_moves() {
+            pos.make_move(mv);
+            let expected = Position::from_str(&pos.to_string()).unwrap();
+            assert_eq!(expected.pawn_hash(), pos.pawn_hash(), "after {mv:?}");
+            assert_eq!(
+                mv.moved() != Some(Piece::WPawn),
+                pos.pawn_hash() == original
+            );
+            pos.unmake_move();
+        }
+        assert_eq!(original, pos.pawn_hash());
+    }
+
+    #[test]
+    fn test_pawn_table() {
+        let mut table = PawnTable::default();
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/3PP3/8/8/8/8/4K3 w - - 0 1",
+        ] {
+            let pos = Position::from_str(fen).unwrap();
+            assert_eq!(pos.pawn_structure(), table.probe(&pos));
+            assert_eq!(pos.pawn_structure(), table.probe(&pos));
+        }
+    }
+}
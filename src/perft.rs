@@ -0,0 +1,93 @@
+use crate::r#move::Move;
+use crate::position::Position;
+
+impl Position {
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep.
+    ///
+    /// Comparing these counts against known values is the standard way of
+    /// verifying move generation and make/unmake.
+    /// See <https://www.chessprogramming.org/Perft>
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_moves();
+
+        // Bulk counting: the leaves below the last ply are exactly the legal
+        // moves, so there is no need to make each of them.
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        moves
+            .into_iter()
+            .map(|mv| {
+                self.make_move(mv);
+                let nodes = self.perft(depth - 1);
+                self.unmake_move();
+                nodes
+            })
+            .sum()
+    }
+
+    /// Runs [`Self::perft`] below each legal move individually, returning
+    /// every root move paired with its leaf node count.
+    ///
+    /// This is useful for narrowing down a perft mismatch to a single move
+    /// by comparing against the output of another engine.
+    pub fn divide(&mut self, depth: u32) -> Vec<(Move, u64)> {
+        if depth == 0 {
+            return Vec::new();
+        }
+
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| {
+                self.make_move(mv);
+                let nodes = self.perft(depth - 1);
+                self.unmake_move();
+                (mv, nodes)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use crate::position::Position;
+    use crate::square::Square;
+
+    #[rstest]
+    #[case::depth_zero(0, 1)]
+    #[case::depth_one(1, 20)]
+    #[case::depth_two(2, 400)]
+    #[case::depth_three(3, 8902)]
+    fn test_perft_starting_position(#[case] depth: u32, #[case] expected: u64) {
+        let mut pos = Position::default();
+        assert_eq!(expected, pos.perft(depth));
+        assert_eq!(Position::default(), pos);
+    }
+
+    #[test]
+    fn test_divide() {
+        let mut pos = Position::from_str(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        let divided = pos.divide(2);
+
+        assert_eq!(48, divided.len());
+        assert_eq!(2039, divided.iter().map(|(_, nodes)| nodes).sum::<u64>());
+
+        let (_, castle) = divided
+            .iter()
+            .find(|(mv, _)| mv.from() == Some(Square::E1) && mv.to() == Some(Square::G1))
+            .expect("kingside castling is legal");
+        assert_eq!(43, *castle);
+    }
+}
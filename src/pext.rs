@@ -0,0 +1,110 @@
+//! Sliding piece attack lookup indexed with the BMI2 `PEXT` instruction.
+//!
+//! `PEXT` gathers the bits of the occupancy selected by a square's relevant
+//! occupancy mask into a dense index, which replaces the multiply-and-shift
+//! hashing of magic bitboards and needs no magic numbers at all.
+//! See <https://www.chessprogramming.org/BMI2#PEXTBitboards>
+
+use std::arch::x86_64::_pext_u64;
+
+use strum::IntoEnumIterator;
+
+use crate::bitboard::Bitboard;
+use crate::direction::RayDirection;
+use crate::magic::{relevant_occupancy, slider_attacks};
+use crate::square::Square;
+
+/// Precomputed attacks of one kind of slider for every square and occupancy.
+///
+/// A [`PextTable`] can only be constructed on CPUs which support BMI2, which
+/// is what makes calling `PEXT` in [`PextTable::get`] sound.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct PextTable {
+    masks: [Bitboard; 64],
+    offsets: [usize; 64],
+    attacks: Vec<Bitboard>,
+}
+
+impl PextTable {
+    /// Builds the attack table for a slider moving along `dirs`, or returns
+    /// [`None`] if the running CPU does not support BMI2.
+    pub(crate) fn new(dirs: &[RayDirection; 4]) -> Option<Self> {
+        if !is_x86_feature_detected!("bmi2") {
+            return None;
+        }
+
+        let mut table = PextTable {
+            masks: [Bitboard::default(); 64],
+            offsets: [0; 64],
+            attacks: Vec::new(),
+        };
+
+        for sq in Square::iter() {
+            let mask = relevant_occupancy(sq, dirs);
+            let offset = table.attacks.len();
+            table.masks[sq as usize] = mask;
+            table.offsets[sq as usize] = offset;
+            table
+                .attacks
+                .resize(offset + (1 << mask.popcount()), Bitboard::default());
+
+            // Enumerate every subset of the mask with the Carry-Rippler trick.
+            let mut occupied = Bitboard::default();
+            loop {
+                // SAFETY: BMI2 support was checked above.
+                let index = unsafe { pext(occupied, mask) };
+                table.attacks[offset + index] = slider_attacks(sq, occupied, dirs);
+
+                occupied = Bitboard::new(occupied.value().wrapping_sub(mask.value())) & mask;
+                if !occupied.bool() {
+                    break;
+                }
+            }
+        }
+
+        Some(table)
+    }
+
+    /// Returns the attacks of a slider on `sq` given the `occupied` squares.
+    pub(crate) fn get(&self, sq: Square, occupied: Bitboard) -> Bitboard {
+        let idx = sq as usize;
+        // SAFETY: a `PextTable` only exists if BMI2 support was detected.
+        let index = unsafe { pext(occupied, self.masks[idx]) };
+        self.attacks[self.offsets[idx] + index]
+    }
+}
+
+#[target_feature(enable = "bmi2")]
+fn pext(occupied: Bitboard, mask: Bitboard) -> usize {
+    _pext_u64(occupied.value(), mask.value()) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+    use strum::IntoEnumIterator;
+
+    use crate::{bitboard::Bitboard, direction::Direction, magic::slider_attacks, square::Square};
+
+    use super::PextTable;
+
+    #[test]
+    #[ignore = "needs a CPU with BMI2, run with --ignored"]
+    fn test_pext_tables_match_ray_walk() {
+        let rooks = PextTable::new(&Direction::ROOK_DIRS).expect("this CPU supports BMI2");
+        let bishops = PextTable::new(&Direction::BISHOP_DIRS).expect("this CPU supports BMI2");
+
+        let mut rng = rand::thread_rng();
+        for sq in Square::iter() {
+            for _ in 0..1000 {
+                let occupied = Bitboard::new(rng.r#gen::<u64>() & rng.r#gen::<u64>());
+
+                let expected = slider_attacks(sq, occupied, &Direction::ROOK_DIRS);
+                assert_eq!(expected, rooks.get(sq, occupied), "rook on {sq}");
+
+                let expected = slider_attacks(sq, occupied, &Direction::BISHOP_DIRS);
+                assert_eq!(expected, bishops.get(sq, occupied), "bishop on {sq}");
+            }
+        }
+    }
+}
@@ -0,0 +1,1131 @@
+//! Portable Game Notation (PGN), the standard format for recording games.
+//!
+//! A PGN file holds any number of games, each made up of tag pairs such as
+//! `[White "Carlsen, Magnus"]` followed by movetext: moves in SAN, with
+//! optional comments, numeric annotation glyphs (NAGs) and variations.
+//! Games are read with a [`PgnReader`] and written with [`Game::to_pgn`].
+//! See <https://www.chessprogramming.org/Portable_Game_Notation>
+//! and the specification at <http://www.saremba.de/chessgml/standards/pgn/pgn-complete.htm>.
+
+use std::io::BufRead;
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+use std::time::Duration;
+
+use crate::color::Color;
+use crate::error::{DiogenesError, DiogenesResult};
+use crate::r#move::Move;
+use crate::position::{Position, STARTING_FEN};
+use crate::search::{Iteration, mate_in};
+
+/// The tags every PGN game must have, in the order they are exported.
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+/// The outcome of a game, as written at the end of its movetext and in its
+/// `Result` tag.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, strum::Display, strum::EnumString)]
+pub enum GameResult {
+    #[strum(serialize = "1-0")]
+    WhiteWins,
+    #[strum(serialize = "0-1")]
+    BlackWins,
+    #[strum(serialize = "1/2-1/2")]
+    Draw,
+    /// The game is still in progress, was abandoned or its result is unknown.
+    #[default]
+    #[strum(serialize = "*")]
+    Unknown,
+}
+
+/// A move of a game along with the annotations attached to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgnMove {
+    pub mv: Move,
+
+    /// Comments written before the move. Only the first move of a game or
+    /// of a variation can have them, any other comment follows a move.
+    pub before: Vec<String>,
+
+    /// Comments written after the move, without their delimiters.
+    pub comments: Vec<String>,
+
+    /// Numeric annotation glyphs, e.g. 1 for `!` and 4 for `??`.
+    pub nags: Vec<u8>,
+
+    /// Alternatives to this move, each starting from the position before it.
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+impl PgnMove {
+    pub fn new(mv: Move) -> Self {
+        Self {
+            mv,
+            before: Vec::new(),
+            comments: Vec::new(),
+            nags: Vec::new(),
+            variations: Vec::new(),
+        }
+    }
+
+    /// Adds a comment with the score, depth and time of the search that
+    /// chose the move, in the format used by most engine match runners,
+    /// e.g. `{+0.35/12 1.52s}` or `{-M3/20 0.08s}`.
+    pub fn annotate(&mut self, iteration: &Iteration) {
+        let score = match mate_in(iteration.score) {
+            Some(moves) if moves < 0 => format!("-M{}", -moves),
+            Some(moves) => format!("+M{moves}"),
+            None => format!("{:+.2}", iteration.score as f64 / 100.0),
+        };
+        self.comments.push(format!(
+            "{score}/{} {:.2}s",
+            iteration.depth,
+            iteration.time.as_secs_f64()
+        ));
+    }
+
+    /// Returns the arguments of the first embedded command called `name` in
+    /// the comments after the move, e.g. `0:03:12` for `{[%clk 0:03:12]}`.
+    pub fn command(&self, name: &str) -> Option<&str> {
+        self.comments
+            .iter()
+            .flat_map(|comment| commands(comment))
+            .find(|(command, _)| *command == name)
+            .map(|(_, args)| args)
+    }
+
+    /// Returns the clock time left after the move, from a `[%clk]` command
+    /// such as `1:02:03` or `0:00:09.5`. Malformed and out of range times
+    /// give [`None`].
+    pub fn clock(&self) -> Option<Duration> {
+        let clock = self.command("clk")?;
+        let (whole, fraction) = match clock.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (clock, None),
+        };
+
+        let mut seconds: u64 = 0;
+        for field in whole.split(':') {
+            seconds = seconds.checked_mul(60)?.checked_add(digits(field)?)?;
+        }
+
+        // Digits beyond nanoseconds are dropped.
+        let nanos = match fraction {
+            Some(fraction) => {
+                digits(fraction)?;
+                format!("{:0<9}", &fraction[..fraction.len().min(9)])
+                    .parse()
+                    .ok()?
+            }
+            None => 0,
+        };
+        Some(Duration::new(seconds, nanos))
+    }
+}
+
+/// Parses `text` if it is a non-empty run of decimal digits.
+fn digits(text: &str) -> Option<u64> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// Returns the name and arguments of every `[%name args]` command embedded
+/// in `comment`.
+fn commands(comment: &str) -> impl Iterator<Item = (&str, &str)> {
+    comment.split("[%").skip(1).filter_map(|command| {
+        let (command, _) = command.split_once(']')?;
+        let (name, args) = command
+            .split_once(char::is_whitespace)
+            .unwrap_or((command, ""));
+        Some((name, args.trim()))
+    })
+}
+
+/// A single game: its tag pairs and its moves from the starting position.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Game {
+    /// The tag pairs in the order they were read.
+    pub tags: Vec<(String, String)>,
+
+    /// The main line, with any variations attached to its moves.
+    pub moves: Vec<PgnMove>,
+
+    /// The result given at the end of the movetext.
+    pub result: GameResult,
+}
+
+impl Game {
+    /// Creates a game without moves played from `start`, which is recorded
+    /// in the `SetUp` and `FEN` tags unless it is the standard starting
+    /// position.
+    pub fn new(start: &Position) -> Self {
+        let mut game = Self::default();
+        let fen = start.to_string();
+        if fen != STARTING_FEN {
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", &fen);
+        }
+        game
+    }
+
+    /// Appends `mv` to the main line, returning it so that it can be
+    /// annotated.
+    pub fn push(&mut self, mv: Move) -> &mut PgnMove {
+        self.moves.push(PgnMove::new(mv));
+        self.moves.last_mut().expect("a move was just pushed")
+    }
+
+    /// Returns the value of the tag called `name`, if the game has one.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the tag called `name` to `value`, replacing any previous value.
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Returns the position the game starts from, which is the standard
+    /// starting position unless the game has a `FEN` tag.
+    pub fn start_position(&self) -> DiogenesResult<Position> {
+        match self.tag("FEN") {
+            Some(fen) => Position::from_str(fen),
+            None => Ok(Position::default()),
+        }
+    }
+
+    /// Returns the position reached at the end of the main line.
+    pub fn end_position(&self) -> DiogenesResult<Position> {
+        let mut pos = self.start_position()?;
+        for mv in &self.moves {
+            pos.make_move(mv.mv);
+        }
+        Ok(pos)
+    }
+
+    /// Writes the game in the PGN export format: the seven tag roster
+    /// first, then any other tags, a blank line and the movetext wrapped at
+    /// 80 columns. Games written one after another must be separated by a
+    /// blank line.
+    pub fn to_pgn(&self) -> DiogenesResult<String> {
+        let mut pgn = String::new();
+        for name in SEVEN_TAG_ROSTER {
+            let value = match name {
+                "Result" => self.result.to_string(),
+                "Date" => self.tag(name).unwrap_or("????.??.??").to_string(),
+                _ => self.tag(name).unwrap_or("?").to_string(),
+            };
+            write_tag(&mut pgn, name, &value);
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                write_tag(&mut pgn, name, value);
+            }
+        }
+        pgn.push('\n');
+
+        let mut tokens = Vec::new();
+        movetext(&mut self.start_position()?, &self.moves, &mut tokens);
+        tokens.push(self.result.to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() {
+                if line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+                    pgn.push_str(&line);
+                    pgn.push('\n');
+                    line.clear();
+                } else {
+                    line.push(' ');
+                }
+            }
+            line.push_str(&token);
+            // A rest-of-line comment ends the line it is on.
+            if line.ends_with('\n') {
+                pgn.push_str(&line);
+                line.clear();
+            }
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+
+        Ok(pgn)
+    }
+}
+
+/// The longest line of movetext in the PGN export format, which asks for
+/// fewer than 80 characters per line.
+const MAX_LINE_LENGTH: usize = 79;
+
+fn write_tag(pgn: &mut String, name: &str, value: &str) {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    pgn.push_str(&format!("[{name} \"{value}\"]\n"));
+}
+
+/// Appends the tokens of the movetext of `moves` played from `pos` to
+/// `tokens`, leaving `pos` where it was.
+fn movetext(pos: &mut Position, moves: &[PgnMove], tokens: &mut Vec<String>) {
+    // Black's moves need their own number after anything that interrupts
+    // the flow of moves, and at the start of a line.
+    let mut interrupted = true;
+    for mv in moves {
+        for comment in &mv.before {
+            push_comment(comment, tokens);
+            interrupted = true;
+        }
+
+        match pos.side_to_move() {
+            Color::White => tokens.push(format!("{}.", pos.fullmove())),
+            Color::Black if interrupted => tokens.push(format!("{}...", pos.fullmove())),
+            Color::Black => {}
+        }
+        tokens.push(pos.to_san(mv.mv));
+        tokens.extend(mv.nags.iter().map(|nag| format!("${nag}")));
+        for comment in &mv.comments {
+            push_comment(comment, tokens);
+        }
+        // The parentheses are part of the first and last token of the
+        // variation, so that a line never ends in `(` or starts with `)`.
+        for variation in &mv.variations {
+            let first = tokens.len();
+            movetext(pos, variation, tokens);
+            match tokens.get_mut(first..) {
+                Some([first, .., last]) if !last.ends_with('\n') => {
+                    first.insert(0, '(');
+                    last.push(')');
+                }
+                Some([only]) if !only.ends_with('\n') => *only = format!("({only})"),
+                Some([first, ..]) => {
+                    first.insert(0, '(');
+                    tokens.push(")".to_string());
+                }
+                _ => tokens.push("()".to_string()),
+            }
+        }
+        interrupted = !mv.comments.is_empty() || !mv.variations.is_empty();
+
+        pos.make_move(mv.mv);
+    }
+
+    for _ in moves {
+        pos.unmake_move();
+    }
+}
+
+/// Appends `comment` to `tokens` word by word, so that long comments can be
+/// wrapped like the rest of the movetext.
+///
+/// A comment containing `}` cannot be written between braces, so it is
+/// written as a rest-of-line comment instead, ending with the newline that
+/// terminates it.
+fn push_comment(comment: &str, tokens: &mut Vec<String>) {
+    if comment.contains('}') {
+        let words: Vec<&str> = comment.split_whitespace().collect();
+        tokens.push(format!("; {}\n", words.join(" ")));
+        return;
+    }
+
+    let mut words: Vec<String> = comment.split_whitespace().map(str::to_string).collect();
+    match &mut words[..] {
+        [] => tokens.push("{}".to_string()),
+        [first, .., last] => {
+            first.insert(0, '{');
+            last.push('}');
+            tokens.extend(words);
+        }
+        [word] => {
+            word.insert(0, '{');
+            word.push('}');
+            tokens.extend(words);
+        }
+    }
+}
+
+/// Decodes a line of a PGN file. The standard calls for ISO 8859-1, but
+/// most files written today are UTF-8, so that is tried first.
+fn decode(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|error| error.into_bytes().into_iter().map(char::from).collect())
+}
+
+/// Reads the games of a PGN file one at a time, so that even very large
+/// databases never have to be held in memory at once.
+///
+/// Each game is parsed on its own: a malformed game yields an error which
+/// can be skipped, after which reading resumes with the next game.
+pub struct PgnReader<R> {
+    input: R,
+
+    /// The number of lines read from the input so far.
+    line: usize,
+
+    /// The first line of the next game, read while looking for the end of
+    /// the previous one.
+    pending: Option<String>,
+
+    /// The number of games read so far.
+    games: usize,
+
+    done: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            line: 0,
+            pending: None,
+            games: 0,
+            done: false,
+        }
+    }
+
+    fn read_line(&mut self) -> DiogenesResult<Option<String>> {
+        if let Some(line) = self.pending.take() {
+            return Ok(Some(line));
+        }
+
+        let mut bytes = Vec::new();
+        match self.input.read_until(b'\n', &mut bytes) {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                self.line += 1;
+                let mut line = decode(bytes);
+                if self.line == 1 {
+                    line = line.trim_start_matches('\u{feff}').to_string();
+                }
+                Ok(Some(line))
+            }
+            Err(error) => Err(DiogenesError::IoError(error.to_string())),
+        }
+    }
+
+    /// Reads the text of the next game, i.e. everything up to the first tag
+    /// pair that follows movetext, and returns it along with the number of
+    /// the line it starts on.
+    fn next_game_text(&mut self) -> DiogenesResult<Option<(usize, String)>> {
+        let mut text = String::new();
+        let mut first_line = self.line;
+        let mut in_movetext = false;
+        let mut in_comment = false;
+
+        while let Some(line) = self.read_line()? {
+            let trimmed = line.trim();
+            if text.trim().is_empty() {
+                // Skip the blank lines between games.
+                first_line = self.line;
+                text.clear();
+            }
+
+            let is_tag = !in_comment && trimmed.starts_with('[');
+            if is_tag && in_movetext {
+                self.pending = Some(line);
+                break;
+            }
+
+            // Lines starting with '%' are escaped and must be ignored.
+            if line.starts_with('%') {
+                text.push('\n');
+                continue;
+            }
+
+            if !is_tag && !trimmed.is_empty() {
+                in_movetext = true;
+                for ch in line.chars() {
+                    match ch {
+                        '}' if in_comment => in_comment = false,
+                        '{' if !in_comment => in_comment = true,
+                        ';' if !in_comment => break,
+                        _ => {}
+                    }
+                }
+            }
+
+            text.push_str(&line);
+            if !line.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+
+        Ok((!text.trim().is_empty()).then_some((first_line, text)))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = DiogenesResult<Game>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_game_text() {
+            Ok(Some((line, text))) => {
+                self.games += 1;
+                Some(Parser::new(&text, self.games, line).game())
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    LeftBracket,
+    RightBracket,
+    LeftParen,
+    RightParen,
+    Period,
+    String(String),
+    Comment(String),
+    Nag(u8),
+    /// Anything else: tag names, moves, move numbers and results.
+    Symbol(String),
+}
+
+/// Parses the text of a single game, keeping track of where each token
+/// starts so that errors can point at it.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    game: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str, game: usize, line: usize) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            game,
+            line,
+            column: 1,
+        }
+    }
+
+    fn error(&self, (line, column): (usize, usize), reason: impl Into<String>) -> DiogenesError {
+        DiogenesError::InvalidPgnError {
+            game: self.game,
+            line,
+            column,
+            reason: reason.into(),
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(ch)
+    }
+
+    /// Reads characters up to and including `end`, returning those before it.
+    fn read_until(&mut self, end: char, at: (usize, usize), what: &str) -> DiogenesResult<String> {
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                Some(ch) if ch == end => return Ok(text),
+                Some(ch) => text.push(ch),
+                None => return Err(self.error(at, format!("unterminated {what}"))),
+            }
+        }
+    }
+
+    /// Returns the next token and the line and column it starts at.
+    fn token(&mut self) -> DiogenesResult<Option<(Token, (usize, usize))>> {
+        while self.chars.peek().is_some_and(|ch| ch.is_whitespace()) {
+            self.bump();
+        }
+
+        let at = (self.line, self.column);
+        let Some(ch) = self.bump() else {
+            return Ok(None);
+        };
+        let token = match ch {
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            '.' => Token::Period,
+            '{' => Token::Comment(self.read_until('}', at, "comment")?.trim().to_string()),
+            ';' => {
+                let comment = self.read_until('\n', at, "comment").unwrap_or_default();
+                Token::Comment(comment.trim().to_string())
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match self.bump() {
+                        Some('"') => break,
+                        Some('\\') => match self.bump() {
+                            Some(ch) => value.push(ch),
+                            None => return Err(self.error(at, "unterminated string")),
+                        },
+                        Some('\n') | None => return Err(self.error(at, "unterminated string")),
+                        Some(ch) => value.push(ch),
+                    }
+                }
+                Token::String(value)
+            }
+            '$' => {
+                let mut digits = String::new();
+                while let Some(digit) = self.chars.next_if(char::is_ascii_digit) {
+                    self.column += 1;
+                    digits.push(digit);
+                }
+                let nag = digits
+                    .parse()
+                    .map_err(|_| self.error(at, format!("invalid NAG ${digits}")))?;
+                Token::Nag(nag)
+            }
+            ch if is_symbol(ch) => {
+                let mut symbol = ch.to_string();
+                while let Some(ch) = self.chars.next_if(|&ch| is_symbol(ch)) {
+                    self.column += 1;
+                    symbol.push(ch);
+                }
+                Token::Symbol(symbol)
+            }
+            ch => return Err(self.error(at, format!("unexpected character {ch:?}"))),
+        };
+
+        Ok(Some((token, at)))
+    }
+
+    fn game(&mut self) -> DiogenesResult<Game> {
+        let mut game = Game::default();
+        let mut pos = Position::default();
+
+        let mut next = self.token()?;
+        while let Some((Token::LeftBracket, at)) = next {
+            let (name, value) = match (self.token()?, self.token()?, self.token()?) {
+                (
+                    Some((Token::Symbol(name), _)),
+                    Some((Token::String(value), _)),
+                    Some((Token::RightBracket, _)),
+                ) => (name, value),
+                _ => return Err(self.error(at, "expected a tag pair like [Name \"value\"]")),
+            };
+            if name == "FEN" {
+                pos = Position::from_str(&value)
+                    .map_err(|error| self.error(at, error.to_string()))?;
+            }
+            game.tags.push((name, value));
+            next = self.token()?;
+        }
+
+        let (moves, result) = self.line_of_play(next, &mut pos, None)?;
+        game.moves = moves;
+        game.result = result.unwrap_or_default();
+
+        match self.token()? {
+            Some((_, at)) => Err(self.error(at, "unexpected text after the result")),
+            None => Ok(game),
+        }
+    }
+
+    /// Parses moves played from `pos` starting with the token `next`, up to
+    /// the end of the game or, for a variation opened by the parenthesis at
+    /// `variation`, its closing parenthesis.
+    fn line_of_play(
+        &mut self,
+        mut next: Option<(Token, (usize, usize))>,
+        pos: &mut Position,
+        variation: Option<(usize, usize)>,
+    ) -> DiogenesResult<(Vec<PgnMove>, Option<GameResult>)> {
+        let mut moves: Vec<PgnMove> = Vec::new();
+        let mut before = Vec::new();
+
+        loop {
+            let Some((token, at)) = next else {
+                if let Some(open) = variation {
+                    return Err(self.error(open, "unterminated variation"));
+                }
+                return Ok((moves, None));
+            };
+
+            match token {
+                Token::Period => {}
+                Token::Comment(comment) => match moves.last_mut() {
+                    Some(last) => last.comments.push(comment),
+                    None => before.push(comment),
+                },
+                Token::Nag(nag) => match moves.last_mut() {
+                    Some(last) => last.nags.push(nag),
+                    None => return Err(self.error(at, "annotation before the first move")),
+                },
+                Token::LeftParen => {
+                    let Some(last) = moves.last_mut() else {
+                        return Err(self.error(at, "variation before the first move"));
+                    };
+                    let mut alternative = pos.clone();
+                    alternative.unmake_move();
+                    let next = self.token()?;
+                    let (line, _) = self.line_of_play(next, &mut alternative, Some(at))?;
+                    last.variations.push(line);
+                }
+                Token::RightParen if variation.is_some() => return Ok((moves, None)),
+                Token::Symbol(symbol) => {
+                    if let Ok(result) = GameResult::from_str(&symbol) {
+                        if variation.is_some() {
+                            return Err(self.error(at, "result inside a variation"));
+                        }
+                        return Ok((moves, Some(result)));
+                    }
+
+                    // Move numbers carry no information beyond the position.
+                    if !symbol.bytes().all(|b| b.is_ascii_digit()) {
+                        let san = symbol.trim_end_matches(['!', '?']);
+                        let nag = annotation_nag(&symbol[san.len()..]).ok_or_else(|| {
+                            self.error(at, format!("invalid annotation {symbol:?}"))
+                        })?;
+                        if !san.is_empty() {
+                            let mv = pos
+                                .parse_san(san)
+                                .map_err(|error| self.error(at, error.to_string()))?;
+                            pos.make_move(mv);
+                            let mut pgn_move = PgnMove::new(mv);
+                            pgn_move.before = std::mem::take(&mut before);
+                            pgn_move.nags.extend(nag);
+                            moves.push(pgn_move);
+                        } else if let Some(last) = moves.last_mut() {
+                            last.nags.extend(nag);
+                        } else {
+                            return Err(self.error(at, "annotation before the first move"));
+                        }
+                    }
+                }
+                Token::RightParen => return Err(self.error(at, "unmatched ')'")),
+                Token::LeftBracket | Token::RightBracket | Token::String(_) => {
+                    return Err(self.error(at, "tag pair inside the movetext"));
+                }
+            }
+
+            next = self.token()?;
+        }
+    }
+}
+
+/// Returns `true` if `ch` can be part of a SAN move, move number, result
+/// or tag name.
+fn is_symbol(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "_+#=:-/!?*".contains(ch)
+}
+
+/// Returns the NAG equivalent to the move annotation `suffix`, e.g. 3 for
+/// `!!`, [`None`] inside the option for an empty suffix and [`None`] if
+/// `suffix` is not an annotation.
+fn annotation_nag(suffix: &str) -> Option<Option<u8>> {
+    match suffix {
+        "" => Some(None),
+        "!" => Some(Some(1)),
+        "?" => Some(Some(2)),
+        "!!" => Some(Some(3)),
+        "??" => Some(Some(4)),
+        "!?" => Some(Some(5)),
+        "?!" => Some(Some(6)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use rstest::rstest;
+
+    use crate::error::DiogenesError;
+    use crate::position::Position;
+    use crate::search::{Iteration, MATE};
+
+    use super::{Game, GameResult, PgnMove, PgnReader};
+
+    fn read(pgn: &str) -> Vec<Result<Game, DiogenesError>> {
+        PgnReader::new(pgn.as_bytes()).collect()
+    }
+
+    fn read_one(pgn: &str) -> Game {
+        let mut games = read(pgn);
+        assert_eq!(1, games.len());
+        games.remove(0).unwrap()
+    }
+
+    /// Formats the main line of `game` in SAN.
+    fn mainline(game: &Game) -> Vec<String> {
+        let mut pos = game.start_position().unwrap();
+        game.moves
+            .iter()
+            .map(|mv| {
+                let san = pos.to_san(mv.mv);
+                pos.make_move(mv.mv);
+                san
+            })
+            .collect()
+    }
+
+    const GAME: &str = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Date "1992.11.04"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+[Annotator "Someone \"quoted\""]
+
+1. e4 {[%clk 1:59:58] The king's pawn} e5 2. Nf3 $1 Nc6 (2... d6 3. d4 (3. Bc4)
+3... exd4) 3. Bb5!? ; The Ruy Lopez
+a6 1/2-1/2
+"#;
+
+    #[test]
+    fn test_read_game() {
+        let game = read_one(GAME);
+        assert_eq!(8, game.tags.len());
+        assert_eq!(Some("Fischer, Robert J."), game.tag("White"));
+        assert_eq!(Some("Someone \"quoted\""), game.tag("Annotator"));
+        assert_eq!(None, game.tag("FEN"));
+        assert_eq!(GameResult::Draw, game.result);
+        assert_eq!(vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"], mainline(&game));
+
+        let e4 = &game.moves[0];
+        assert_eq!(vec!["[%clk 1:59:58] The king's pawn"], e4.comments);
+        assert_eq!(Some("1:59:58"), e4.command("clk"));
+        assert_eq!(Some(Duration::from_secs(7198)), e4.clock());
+        assert_eq!(None, game.moves[1].clock());
+
+        assert_eq!(vec![1], game.moves[2].nags);
+        assert_eq!(vec![5], game.moves[4].nags);
+        assert_eq!(vec!["The Ruy Lopez"], game.moves[4].comments);
+
+        let variations = &game.moves[3].variations;
+        assert_eq!(1, variations.len());
+        let [d6, d4, exd4] = &variations[0][..] else {
+            panic!("unexpected variation {variations:?}");
+        };
+        assert_eq!("d7d6", d6.mv.to_string());
+        assert_eq!("d2d4", d4.mv.to_string());
+        assert_eq!("e5d4", exd4.mv.to_string());
+        assert_eq!("f1c4", d4.variations[0][0].mv.to_string());
+    }
+
+    #[rstest]
+    #[case::hours("1:02:03", Some(Duration::from_secs(3723)))]
+    #[case::seconds_only("59", Some(Duration::from_secs(59)))]
+    #[case::fraction("0:00:09.5", Some(Duration::from_millis(9500)))]
+    #[case::fine_fraction("0:00:00.1234567891", Some(Duration::from_nanos(123_456_789)))]
+    #[case::infinite("inf", None)]
+    #[case::exponent("1e30", None)]
+    #[case::negative("-0:01:00", None)]
+    #[case::fraction_not_last("1.5:00", None)]
+    #[case::empty_field("1::00", None)]
+    #[case::empty_fraction("0:00:09.", None)]
+    #[case::overflow("99999999999999999999", None)]
+    #[case::too_large("999999999999999999:00:00", None)]
+    fn test_clock(#[case] clock: &str, #[case] expected: Option<Duration>) {
+        let game = read_one(&format!("1. e4 {{[%clk {clock}]}} e5 *"));
+        assert_eq!(expected, game.moves[0].clock());
+    }
+
+    #[test]
+    fn test_read_from_position() {
+        let game = read_one(
+            "[FEN \"4k3/P7/8/8/8/8/8/4K3 w - - 0 1\"]\n[SetUp \"1\"]\n\n\
+             {Promotion} 1. a8=Q+ Kd7 *",
+        );
+        assert_eq!(vec!["a8=Q+", "Kd7"], mainline(&game));
+        assert_eq!(vec!["Promotion"], game.moves[0].before);
+        assert_eq!(GameResult::Unknown, game.result);
+        assert_eq!(
+            "Q7/3k4/8/8/8/8/8/4K3 w - - 1 2",
+            game.end_position().unwrap().to_string()
+        );
+    }
+
+    #[rstest]
+    #[case::no_tags("1. e4 e5 1-0", vec!["e4", "e5"], GameResult::WhiteWins)]
+    #[case::no_result("1. d4", vec!["d4"], GameResult::Unknown)]
+    #[case::no_spaces("1.e4 e5 2.Nf3 0-1", vec!["e4", "e5", "Nf3"], GameResult::BlackWins)]
+    #[case::black_move_number("1. e4 {Best by test} 1... c5 *", vec!["e4", "c5"], GameResult::Unknown)]
+    #[case::escaped_line("1. e4\n% 1. d4\ne5 *", vec!["e4", "e5"], GameResult::Unknown)]
+    #[case::castling(
+        "1. e4 e5 2. Nf3 Nf6 3. Bc4 Bc5 4. O-O 0-0 *",
+        vec!["e4", "e5", "Nf3", "Nf6", "Bc4", "Bc5", "O-O", "O-O"],
+        GameResult::Unknown
+    )]
+    fn test_read_movetext(
+        #[case] pgn: &str,
+        #[case] expected: Vec<&str>,
+        #[case] result: GameResult,
+    ) {
+        let game = read_one(pgn);
+        assert_eq!(expected, mainline(&game));
+        assert_eq!(result, game.result);
+    }
+
+    #[test]
+    fn test_read_many_games() {
+        let pgn =
+            format!("{GAME}\n\n[Event \"Second\"]\n\n1. d4 d5 *\n\n\n[Event \"Third\"]\n1. c4 *\n");
+        let games: Vec<Game> = read(&pgn).into_iter().map(Result::unwrap).collect();
+        assert_eq!(3, games.len());
+        assert_eq!(Some("Second"), games[1].tag("Event"));
+        assert_eq!(vec!["d4", "d5"], mainline(&games[1]));
+        assert_eq!(vec!["c4"], mainline(&games[2]));
+    }
+
+    #[rstest]
+    #[case::illegal_move(
+        "1. e4 e5 2. Ke3 *",
+        1,
+        13,
+        "invalid SAN move \"Ke3\": no legal move matches"
+    )]
+    #[case::bad_tag("[Event \"Unclosed]\n\n1. e4 *", 1, 8, "unterminated string")]
+    #[case::missing_tag_value(
+        "[Event]\n\n1. e4 *",
+        1,
+        1,
+        "expected a tag pair like [Name \"value\"]"
+    )]
+    #[case::unterminated_comment("1. e4 {Never\nclosed *", 1, 7, "unterminated comment")]
+    #[case::result_inside_variation("1. e4 (1. d4 *", 1, 14, "result inside a variation")]
+    #[case::unterminated_variation("1. e4 (1. d4", 1, 7, "unterminated variation")]
+    #[case::unterminated_nested_variation("1. e4 (1. d4 (1. c4)\n", 1, 7, "unterminated variation")]
+    #[case::unmatched_paren("1. e4 ) *", 1, 7, "unmatched ')'")]
+    #[case::early_variation("(1. d4) 1. e4 *", 1, 1, "variation before the first move")]
+    #[case::bad_nag("1. e4 $256 *", 1, 7, "invalid NAG $256")]
+    #[case::bad_annotation("1. e4!!! *", 1, 4, "invalid annotation \"e4!!!\"")]
+    #[case::trailing_text("1. e4 * e5", 1, 9, "unexpected text after the result")]
+    #[case::bad_character("1. e4 & *", 1, 7, "unexpected character '&'")]
+    fn test_read_invalid_game(
+        #[case] pgn: &str,
+        #[case] line: usize,
+        #[case] column: usize,
+        #[case] reason: &str,
+    ) {
+        assert_eq!(
+            vec![Err(DiogenesError::InvalidPgnError {
+                game: 1,
+                line,
+                column,
+                reason: reason.to_string(),
+            })],
+            read(pgn)
+        );
+    }
+
+    #[test]
+    fn test_invalid_games_are_skipped() {
+        let pgn = "[Event \"A\"]\n\n1. e4 *\n\n\
+                   [Event \"B\"]\n[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n\n1. e4 *\n\n\
+                   [Event \"C\"]\n\n1. e4 e5\n2. Nf3 Nf3 *\n\n\
+                   [Event \"D\"]\n\n1. d4 *\n";
+        let games = read(pgn);
+        assert_eq!(4, games.len());
+        assert_eq!(Some("A"), games[0].as_ref().unwrap().tag("Event"));
+        assert!(matches!(
+            games[1],
+            Err(DiogenesError::InvalidPgnError {
+                game: 2,
+                line: 6,
+                column: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            games[2],
+            Err(DiogenesError::InvalidPgnError {
+                game: 3,
+                line: 13,
+                column: 8,
+                ..
+            })
+        ));
+        assert_eq!(Some("D"), games[3].as_ref().unwrap().tag("Event"));
+    }
+
+    #[test]
+    fn test_read_latin1() {
+        let mut pgn = b"[White \"M\xfcller\"]\n\n1. e4 {gr\xfc\xdfe} *\n\n".to_vec();
+        pgn.extend_from_slice("[White \"Müller\"]\n\n1. d4 *\n".as_bytes());
+        let games: Vec<Game> = PgnReader::new(&pgn[..]).map(Result::unwrap).collect();
+        assert_eq!(2, games.len());
+        assert_eq!(Some("Müller"), games[0].tag("White"));
+        assert_eq!(vec!["grüße"], games[0].moves[0].comments);
+        assert_eq!(Some("Müller"), games[1].tag("White"));
+    }
+
+    #[test]
+    fn test_read_empty_input() {
+        assert!(read("").is_empty());
+        assert!(read("\n\n  \n").is_empty());
+    }
+
+    fn push_san(game: &mut Game, moves: &[&str]) {
+        let mut pos = game.end_position().unwrap();
+        for san in moves {
+            let mv = pos.parse_san(san).unwrap();
+            pos.make_move(mv);
+            game.push(mv);
+        }
+    }
+
+    #[test]
+    fn test_write_game() {
+        let mut game = Game::new(&Position::default());
+        game.set_tag("White", "Diogenes");
+        game.set_tag("Black", "Diogenes \"dev\"");
+        game.set_tag("TimeControl", "60+1");
+        push_san(&mut game, &["e4", "e5", "Nf3"]);
+        game.moves[0].annotate(&iteration(35, 12, 1520));
+        game.moves[1].nags.push(6);
+        let mut pos = Position::default();
+        pos.make_move(game.moves[0].mv);
+        let c5 = PgnMove::new(pos.parse_san("c5").unwrap());
+        game.moves[1].variations.push(vec![c5]);
+        game.result = GameResult::WhiteWins;
+
+        assert_eq!(
+            "[Event \"?\"]\n\
+             [Site \"?\"]\n\
+             [Date \"????.??.??\"]\n\
+             [Round \"?\"]\n\
+             [White \"Diogenes\"]\n\
+             [Black \"Diogenes \\\"dev\\\"\"]\n\
+             [Result \"1-0\"]\n\
+             [TimeControl \"60+1\"]\n\
+             \n\
+             1. e4 {+0.35/12 1.52s} 1... e5 $6 (1... c5) 2. Nf3 1-0\n",
+            game.to_pgn().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_from_position() {
+        let start = Position::from_str("4k3/8/8/8/8/8/p7/4K3 b - - 3 41").unwrap();
+        let mut game = Game::new(&start);
+        push_san(&mut game, &["a1=Q+", "Kd2", "Qb2+"]);
+
+        let pgn = game.to_pgn().unwrap();
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/p7/4K3 b - - 3 41\"]\n"));
+        assert!(pgn.ends_with("\n\n41... a1=Q+ 42. Kd2 Qb2+ *\n"), "{pgn}");
+        assert_eq!(None, Game::new(&Position::default()).tag("FEN"));
+    }
+
+    #[test]
+    fn test_write_wraps_lines() {
+        let mut game = Game::new(&Position::default());
+        for _ in 0..20 {
+            push_san(&mut game, &["Nf3", "Nf6", "Ng1", "Ng8"]);
+        }
+        game.moves[10].comments.push("a long comment ".repeat(10));
+
+        let pgn = game.to_pgn().unwrap();
+        let movetext = pgn.split_once("\n\n").unwrap().1;
+        assert!(movetext.lines().count() > 1);
+        assert!(movetext.lines().all(|line| line.len() < 80), "{pgn}");
+        assert!(movetext.lines().any(|line| line.len() > 70), "{pgn}");
+
+        let read = read_one(&pgn);
+        assert_eq!(game.moves.len(), read.moves.len());
+        assert_eq!(
+            "a long comment "
+                .repeat(10)
+                .split_whitespace()
+                .collect::<Vec<_>>(),
+            read.moves[10].comments[0]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_write_wraps_variations() {
+        // Every move has a variation, so that some variation is bound to
+        // start or end right at the wrapping column.
+        let mut game = Game::new(&Position::default());
+        let mut pos = Position::default();
+        for _ in 0..10 {
+            for (san, alternative) in [
+                ("Nf3", "Nh3"),
+                ("Nf6", "Nh6"),
+                ("Ng1", "Nd4"),
+                ("Ng8", "Nd5"),
+            ] {
+                let variation = PgnMove::new(pos.parse_san(alternative).unwrap());
+                let mv = pos.parse_san(san).unwrap();
+                pos.make_move(mv);
+                game.push(mv).variations.push(vec![variation]);
+            }
+        }
+
+        let pgn = game.to_pgn().unwrap();
+        let movetext = pgn.split_once("\n\n").unwrap().1;
+        for line in movetext.lines() {
+            assert!(line.len() < 80, "{line:?} in\n{pgn}");
+            assert!(
+                !line.ends_with('(') && !line.starts_with(')'),
+                "{line:?} in\n{pgn}"
+            );
+        }
+        assert_eq!(game.moves, read_one(&pgn).moves);
+    }
+
+    #[rstest]
+    #[case::game(GAME)]
+    #[case::closing_brace("1. e4 ; see } here\n1... e5 *")]
+    #[case::closing_brace_ends_variation("1. e4 (1. d4 ; a } b\n) e5 *")]
+    fn test_write_read_round_trip(#[case] input: &str) {
+        let game = read_one(input);
+        let pgn = game.to_pgn().unwrap();
+        let written = read_one(&pgn);
+        // The writer fills in the Seven Tag Roster, so only the tags of the
+        // original game need to survive.
+        for (name, value) in &game.tags {
+            assert_eq!(written.tag(name), Some(value.as_str()), "{pgn}");
+        }
+        assert_eq!(game.moves, written.moves, "{pgn}");
+        assert_eq!(game.result, written.result, "{pgn}");
+    }
+
+    fn iteration(score: i32, depth: u32, millis: u64) -> Iteration {
+        Iteration {
+            depth,
+            score,
+            nodes: 0,
+            time: Duration::from_millis(millis),
+            pv: Vec::new(),
+        }
+    }
+
+    #[rstest]
+    #[case::advantage(iteration(35, 12, 1520), "+0.35/12 1.52s")]
+    #[case::disadvantage(iteration(-120, 9, 80), "-1.20/9 0.08s")]
+    #[case::even(iteration(0, 1, 0), "+0.00/1 0.00s")]
+    #[case::mating(iteration(MATE - 5, 20, 2000), "+M3/20 2.00s")]
+    #[case::mated(iteration(-MATE + 4, 20, 2000), "-M2/20 2.00s")]
+    fn test_annotate(#[case] iteration: Iteration, #[case] comment: &str) {
+        let mut mv = PgnMove::new(Position::default().parse_san("e4").unwrap());
+        mv.annotate(&iteration);
+        assert_eq!(vec![comment], mv.comments);
+    }
+}
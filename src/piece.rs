@@ -5,7 +5,16 @@ use num_derive::{FromPrimitive, ToPrimitive};
 use crate::{color::Color, error::DiogenesError};
 
 #[derive(
-    Clone, Copy, Debug, PartialEq, strum::EnumIter, strum::EnumCount, FromPrimitive, ToPrimitive,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::EnumIter,
+    strum::EnumCount,
+    FromPrimitive,
+    ToPrimitive,
 )]
 pub enum Piece {
     WPawn,
@@ -119,12 +128,33 @@ impl Piece {
             | Self::BKing => Color::Black,
         }
     }
+
+    /// Returns the piece of the same type as `self` belonging to `color`,
+    /// e.g. [`Piece::WKnight`] becomes [`Piece::BKnight`] for [`Color::Black`].
+    pub fn with_color(&self, color: Color) -> Piece {
+        match (self, color) {
+            (Self::WPawn | Self::BPawn, Color::White) => Self::WPawn,
+            (Self::WKnight | Self::BKnight, Color::White) => Self::WKnight,
+            (Self::WBishop | Self::BBishop, Color::White) => Self::WBishop,
+            (Self::WRook | Self::BRook, Color::White) => Self::WRook,
+            (Self::WQueen | Self::BQueen, Color::White) => Self::WQueen,
+            (Self::WKing | Self::BKing, Color::White) => Self::WKing,
+            (Self::WPawn | Self::BPawn, Color::Black) => Self::BPawn,
+            (Self::WKnight | Self::BKnight, Color::Black) => Self::BKnight,
+            (Self::WBishop | Self::BBishop, Color::Black) => Self::BBishop,
+            (Self::WRook | Self::BRook, Color::Black) => Self::BRook,
+            (Self::WQueen | Self::BQueen, Color::Black) => Self::BQueen,
+            (Self::WKing | Self::BKing, Color::Black) => Self::BKing,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
+    use crate::color::Color;
+
     use super::Piece;
     #[test]
     fn test_str() {
@@ -154,4 +184,11 @@ mod tests {
             assert_eq!(val, p);
         }
     }
+
+    #[test]
+    fn test_with_color() {
+        assert_eq!(Piece::WKnight.with_color(Color::Black), Piece::BKnight);
+        assert_eq!(Piece::BQueen.with_color(Color::White), Piece::WQueen);
+        assert_eq!(Piece::WKing.with_color(Color::White), Piece::WKing);
+    }
 }
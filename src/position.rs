@@ -9,6 +9,7 @@ use std::str::FromStr;
 use num_traits::FromPrimitive;
 use strum::IntoEnumIterator;
 
+use crate::attacks::ATTACKS;
 use crate::bitboard::Bitboard;
 use crate::board;
 use crate::board::{File, Rank};
@@ -16,11 +17,16 @@ use crate::castling::CastlingRights;
 use crate::color::Color;
 use crate::error::DiogenesError;
 use crate::error::DiogenesResult;
+use crate::error::FenField;
+use crate::eval;
+use crate::eval::Score;
 use crate::r#move::Move;
 use crate::piece::Piece;
 use crate::square::Square;
+use crate::zobrist::KEYS;
 
-const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+/// The FEN string of the standard starting position.
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
 /// Represents a denormalized set of bitboards that represent
 /// all chess pieces on the board in a given position.
@@ -65,42 +71,81 @@ impl FromStr for PieceSet {
     type Err = DiogenesError;
 
     /// Parses a [`PieceSet`] from a FEN (Forsyth-Edwards Notation) input string.
+    ///
+    /// The placement must describe exactly eight ranks of exactly eight files
+    /// each, from the eighth rank down to the first, where empty squares are
+    /// counted by a single digit from 1 to 8.
     fn from_str(fen: &str) -> DiogenesResult<Self> {
+        let ranks: Vec<&str> = fen.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(DiogenesError::InvalidFenFieldError {
+                field: FenField::Placement,
+                value: fen.to_string(),
+                reason: format!("expected 8 ranks but found {}", ranks.len()),
+            });
+        }
+
         let mut val = Self([Bitboard::default(); 14]);
-        fen.rsplit("/")
-            .zip(Rank::iter())
-            .try_for_each(|(pieces, rank)| {
-                let mut f: u32 = 0;
-                for ch in pieces.chars() {
-                    match ch.to_digit(10) {
-                        Some(skip) => {
-                            if skip != 8 {
-                                f += skip;
-                            }
-                        }
-                        None => {
-                            let file = File::from_u32(f).ok_or(DiogenesError::InvalidFenError {
-                                fen: fen.to_string(),
-                                reason: format!("invalid file: {f:?}"),
+        let mut index = 0;
+        for (pieces, rank) in ranks.into_iter().zip(Rank::iter().rev()) {
+            let rank_number = rank as usize + 1;
+            let mut f: u32 = 0;
+            let mut after_digit = false;
+            for ch in pieces.chars() {
+                let err = |reason: String| DiogenesError::InvalidFenCharacterError {
+                    field: FenField::Placement,
+                    index,
+                    ch,
+                    reason,
+                };
+                match ch.to_digit(10) {
+                    Some(_) if after_digit => {
+                        return Err(err("empty squares must be counted by a single digit".into()));
+                    }
+                    Some(skip @ 1..=8) => {
+                        f += skip;
+                        after_digit = true;
+                    }
+                    Some(_) => {
+                        return Err(err("empty squares must be counted from 1 to 8".into()));
+                    }
+                    None => {
+                        let piece = Piece::try_from(ch)
+                            .map_err(|_| err("expected a piece or a digit".into()))?;
+                        let color = match ch.is_ascii_uppercase() {
+                            true => Color::White,
+                            false => Color::Black,
+                        };
+                        let sq_idx = File::from_u32(f)
+                            .and_then(|file| board::try_index(file, rank).ok())
+                            .ok_or_else(|| {
+                                err(format!("rank {rank_number} has more than 8 files"))
                             })?;
-                            let color = match ch.is_ascii_uppercase() {
-                                true => Color::White,
-                                false => Color::Black,
-                            };
-                            let piece = Piece::try_from(ch)?;
-                            let sq_idx: usize =
-                                board::try_index(file, rank).expect("invalid file + rank index");
-
-                            let bb: u64 = 1 << sq_idx;
-
-                            val[piece] |= bb;
-                            val[color] |= bb;
-                            f += 1;
-                        }
+
+                        let bb: u64 = 1 << sq_idx;
+
+                        val[piece] |= bb;
+                        val[color] |= bb;
+                        f += 1;
+                        after_digit = false;
                     }
                 }
-                Ok::<(), DiogenesError>(())
-            })?;
+                if f > 8 {
+                    return Err(err(format!("rank {rank_number} has more than 8 files")));
+                }
+                index += ch.len_utf8();
+            }
+
+            if f < 8 {
+                return Err(DiogenesError::InvalidFenFieldError {
+                    field: FenField::Placement,
+                    value: fen.to_string(),
+                    reason: format!("rank {rank_number} has {f} files, expected 8"),
+                });
+            }
+            // Skip past the '/' separator.
+            index += 1;
+        }
 
         Ok(val)
     }
@@ -166,6 +211,26 @@ impl IndexMut<Color> for PieceSet {
     }
 }
 
+/// State that cannot be recovered from a [`Move`] alone, saved
+/// when a move is made so that it can be unmade exactly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Undo {
+    /// The move that was made, or [`Move::NULL`] for a null move.
+    mv: Move,
+
+    /// The en-passant square before the move was made.
+    ep: Option<Square>,
+
+    /// The castling rights before the move was made.
+    castling_rights: CastlingRights,
+
+    /// The halfmove clock before the move was made.
+    halfmove: i32,
+
+    /// The Zobrist hash before the move was made.
+    hash: u64,
+}
+
 /// The fundamental construct for the engine that keeps track of the board representation.
 #[derive(Clone, PartialEq, Eq)]
 pub struct Position {
@@ -185,11 +250,10 @@ pub struct Position {
     /// The current number of full, completed moves.
     fullmove: i32,
 
-    /// The current ply of the position, which is the number
-    /// of half-moves. Should usually be double the full move
-    /// count.
-    /// See <https://www.chessprogramming.org/Ply>
-    ply: i32,
+    /// The number of half-moves since the last capture or pawn move,
+    /// used to apply the fifty-move rule.
+    /// See <https://www.chessprogramming.org/Halfmove_Clock>
+    halfmove: i32,
 
     /// Whose turn it is to move - [`Color::White`] or [`Color::Black`]
     side_to_move: Color,
@@ -205,8 +269,25 @@ pub struct Position {
     /// either direction.
     castling_rights: CastlingRights,
 
-    /// The list of all made moves in the current game.
-    history: VecDeque<Move>,
+    /// The list of all made moves in the current game, along with the
+    /// irreversible state needed to unmake each of them.
+    history: VecDeque<Undo>,
+
+    /// The Zobrist hash of the position, kept up to date incrementally as
+    /// moves are made. See [`crate::zobrist`].
+    hash: u64,
+
+    /// The Zobrist hash of the pawns alone, kept up to date incrementally
+    /// as moves are made. See [`crate::pawns`].
+    pawn_hash: u64,
+
+    /// The material and piece-square score of the position from White's
+    /// point of view, kept up to date incrementally as moves are made.
+    psqt: Score,
+
+    /// The game phase, derived from the non-pawn material on the board and
+    /// kept up to date incrementally as moves are made.
+    phase: i32,
 }
 
 impl Debug for Position {
@@ -257,6 +338,36 @@ impl Default for Position {
 }
 
 impl Position {
+    /// Returns the set of bitboards for every piece on the board.
+    pub fn pieces(&self) -> &PieceSet {
+        &self.pieces
+    }
+
+    /// Returns all currently empty squares.
+    pub fn empty(&self) -> Bitboard {
+        self.empty
+    }
+
+    /// Returns all currently occupied squares.
+    pub fn occupied(&self) -> Bitboard {
+        self.occupied
+    }
+
+    /// Returns the [`Color`] whose turn it is to move.
+    pub fn side_to_move(&self) -> Color {
+        self.side_to_move
+    }
+
+    /// Returns the square a pawn could capture onto en-passant, if any.
+    pub fn ep(&self) -> Option<Square> {
+        self.ep
+    }
+
+    /// Returns the castling rights of both sides.
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling_rights
+    }
+
     /// Retrieves the [`crate::piece::Piece`] at the specific index.
     /// Returns [`None`] if the square is empty.
     pub fn piece<S: Into<Square>>(&self, square: S) -> Option<Piece> {
@@ -307,6 +418,395 @@ impl Position {
         }
     }
 
+    /// Returns the number of half-moves since the last capture or pawn move.
+    pub fn halfmove(&self) -> i32 {
+        self.halfmove
+    }
+
+    /// Returns the number of the current full move, starting at 1.
+    pub fn fullmove(&self) -> i32 {
+        self.fullmove
+    }
+
+    /// Returns the square of the king of `color`.
+    ///
+    /// Every valid position has exactly one king per side, so this panics
+    /// if there is none.
+    pub fn king_square(&self, color: Color) -> Square {
+        self.pieces[Piece::WKing.with_color(color)]
+            .lsb()
+            .expect("each side has a king")
+    }
+
+    /// Returns a [`Bitboard`] of all pieces of either color that attack `sq`
+    /// given the `occupancy`, which may differ from the real occupancy of the
+    /// board to look through (or add) blockers of sliding pieces.
+    pub fn attackers_to(&self, sq: Square, occupancy: Bitboard) -> Bitboard {
+        let pieces = &self.pieces;
+        let knights = pieces[Piece::WKnight] | pieces[Piece::BKnight];
+        let kings = pieces[Piece::WKing] | pieces[Piece::BKing];
+        let queens = pieces[Piece::WQueen] | pieces[Piece::BQueen];
+        let rooks = pieces[Piece::WRook] | pieces[Piece::BRook] | queens;
+        let bishops = pieces[Piece::WBishop] | pieces[Piece::BBishop] | queens;
+
+        (ATTACKS.pawns(sq, Color::Black) & pieces[Piece::WPawn])
+            | (ATTACKS.pawns(sq, Color::White) & pieces[Piece::BPawn])
+            | (ATTACKS.knight(sq, Bitboard::default()) & knights)
+            | (ATTACKS.king(sq, Bitboard::default()) & kings)
+            | (ATTACKS.rook(sq, occupancy) & rooks)
+            | (ATTACKS.bishop(sq, occupancy) & bishops)
+    }
+
+    /// Returns `true` if any piece of `color` attacks `sq`.
+    pub fn is_attacked(&self, sq: Square, color: Color) -> bool {
+        (self.attackers_to(sq, self.occupied) & self.pieces[color]).bool()
+    }
+
+    /// Returns a [`Bitboard`] of the enemy pieces giving check to the king
+    /// of the side to move.
+    pub fn checkers(&self) -> Bitboard {
+        let king = self.king_square(self.side_to_move);
+        self.attackers_to(king, self.occupied) & self.pieces[!self.side_to_move]
+    }
+
+    /// Returns `true` if the king of the side to move is in check.
+    pub fn in_check(&self) -> bool {
+        self.checkers().bool()
+    }
+
+    /// Returns the Zobrist hash of the position, which identifies it (with
+    /// overwhelming probability) for repetition detection and hash tables.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns the Zobrist hash of the pawns of both sides alone, which
+    /// identifies the pawn skeleton of the position.
+    pub fn pawn_hash(&self) -> u64 {
+        self.pawn_hash
+    }
+
+    /// Returns the tapered material and piece-square score of the position
+    /// from White's point of view. See [`crate::eval::psqt`].
+    pub fn psqt(&self) -> Score {
+        self.psqt
+    }
+
+    /// Returns the game phase, which drops from [`crate::eval::MAX_PHASE`]
+    /// towards zero as non-pawn pieces are traded off.
+    pub fn phase(&self) -> i32 {
+        self.phase
+    }
+
+    /// Returns the most recently made move, or [`None`] if no moves have
+    /// been made on this position. Null moves are returned as [`Move::NULL`].
+    pub fn last_move(&self) -> Option<Move> {
+        self.history.back().map(|undo| undo.mv)
+    }
+
+    /// Returns `true` if the position repeats one reached earlier with the
+    /// same side to move since the last capture or pawn move, for a search
+    /// whose root lies `ply` moves back.
+    ///
+    /// Repeating a position reached after the root once is enough for the
+    /// search to score a draw, since whichever side could avoid it would
+    /// have done so already. Positions from before the root were played in
+    /// the game itself, so repeating those takes a true threefold repetition.
+    pub fn is_repetition(&self, ply: usize) -> bool {
+        let mut repetitions = 0;
+        for (index, undo) in self
+            .history
+            .iter()
+            .rev()
+            .enumerate()
+            .take(self.halfmove as usize)
+        {
+            // The position before the move `index + 1` plies back.
+            let distance = index + 1;
+            if distance % 2 == 0 && undo.hash == self.hash {
+                repetitions += 1;
+                if distance < ply || repetitions == 2 {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns `true` if the position is drawn by repetition or by the
+    /// fifty-move rule, for a search whose root lies `ply` moves back.
+    pub fn is_draw(&self, ply: usize) -> bool {
+        self.halfmove >= 100 || self.is_repetition(ply)
+    }
+
+    /// Plays `mv` on the board, updating the pieces, side to move,
+    /// en-passant square, castling rights and move counters.
+    ///
+    /// `mv` is assumed to be a (pseudo-)legal move in this position as
+    /// produced by move generation; the move is not validated.
+    pub fn make_move(&mut self, mv: Move) {
+        let us = self.side_to_move;
+        let from = mv.from().expect("move has a valid source square");
+        let to = mv.to().expect("move has a valid target square");
+        let moved = mv.moved().expect("move has a valid moved piece");
+
+        self.history.push_back(Undo {
+            mv,
+            ep: self.ep,
+            castling_rights: self.castling_rights,
+            halfmove: self.halfmove,
+            hash: self.hash,
+        });
+
+        // Whether the en-passant square is hashed depends on the pieces, so
+        // its key is removed before any of them move.
+        self.hash ^= self.ep_key();
+
+        if let Some(captured) = mv.captured() {
+            self.toggle(captured, Self::capture_square(mv, to));
+        }
+
+        self.toggle(moved, from);
+        self.toggle(mv.promoted().unwrap_or(moved), to);
+
+        if mv.is_castle() {
+            let (rook, rook_from, rook_to) = Self::castling_rook(mv, to);
+            self.toggle(rook, rook_from);
+            self.toggle(rook, rook_to);
+        }
+
+        self.hash ^= KEYS.castling(self.castling_rights);
+        self.ep = match mv.is_double_pawn_push() {
+            true => Square::from_u8((from as u8 + to as u8) / 2),
+            false => None,
+        };
+        self.castling_rights = self.castling_rights.revoke(from).revoke(to);
+        self.hash ^= KEYS.castling(self.castling_rights);
+
+        let is_pawn = moved == Piece::WPawn.with_color(us);
+        self.halfmove = match is_pawn || mv.capture() {
+            true => 0,
+            false => self.halfmove + 1,
+        };
+        if us == Color::Black {
+            self.fullmove += 1;
+        }
+        self.side_to_move = !us;
+        self.hash ^= KEYS.side() ^ self.ep_key();
+
+        debug_assert_eq!(
+            self.hash,
+            self.compute_hash(),
+            "incremental hash diverged after {mv:?}"
+        );
+        debug_assert_eq!(
+            self.pawn_hash,
+            self.compute_pawn_hash(),
+            "incremental pawn hash diverged after {mv:?}"
+        );
+        debug_assert_eq!(
+            (self.psqt, self.phase),
+            self.compute_psqt(),
+            "incremental evaluation diverged after {mv:?}"
+        );
+    }
+
+    /// Takes back the most recently made move, restoring the position
+    /// exactly as it was before [`Self::make_move`] was called.
+    ///
+    /// Returns the move that was unmade, or [`None`] if there were no
+    /// moves to unmake.
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let undo = self.history.pop_back()?;
+        let mv = undo.mv;
+        debug_assert!(!mv.is_null(), "use unmake_null_move to unmake a null move");
+
+        let us = !self.side_to_move;
+        let from = mv.from().expect("move has a valid source square");
+        let to = mv.to().expect("move has a valid target square");
+        let moved = mv.moved().expect("move has a valid moved piece");
+
+        if mv.is_castle() {
+            let (rook, rook_from, rook_to) = Self::castling_rook(mv, to);
+            self.toggle(rook, rook_to);
+            self.toggle(rook, rook_from);
+        }
+
+        self.toggle(mv.promoted().unwrap_or(moved), to);
+        self.toggle(moved, from);
+
+        if let Some(captured) = mv.captured() {
+            self.toggle(captured, Self::capture_square(mv, to));
+        }
+
+        if us == Color::Black {
+            self.fullmove -= 1;
+        }
+        self.side_to_move = us;
+        self.ep = undo.ep;
+        self.castling_rights = undo.castling_rights;
+        self.halfmove = undo.halfmove;
+        self.hash = undo.hash;
+
+        Some(mv)
+    }
+
+    /// Passes the turn to the opponent without moving a piece.
+    ///
+    /// Null moves are not legal chess moves, but are useful during search
+    /// to see whether a position is good enough that even giving the
+    /// opponent a free move does not help them.
+    pub fn make_null_move(&mut self) {
+        self.history.push_back(Undo {
+            mv: Move::NULL,
+            ep: self.ep,
+            castling_rights: self.castling_rights,
+            halfmove: self.halfmove,
+            hash: self.hash,
+        });
+
+        self.hash ^= self.ep_key() ^ KEYS.side();
+        self.ep = None;
+        self.halfmove += 1;
+        if self.side_to_move == Color::Black {
+            self.fullmove += 1;
+        }
+        self.side_to_move = !self.side_to_move;
+    }
+
+    /// Takes back a null move made by [`Self::make_null_move`].
+    pub fn unmake_null_move(&mut self) {
+        let undo = self
+            .history
+            .pop_back()
+            .expect("a null move must be made before it is unmade");
+        debug_assert!(undo.mv.is_null(), "use unmake_move to unmake a move");
+
+        self.side_to_move = !self.side_to_move;
+        if self.side_to_move == Color::Black {
+            self.fullmove -= 1;
+        }
+        self.ep = undo.ep;
+        self.halfmove = undo.halfmove;
+        self.hash = undo.hash;
+    }
+
+    /// Adds `piece` to `sq` if the square is empty, or removes it if
+    /// `piece` is already on `sq`.
+    fn toggle(&mut self, piece: Piece, sq: Square) {
+        let bb = sq.bitboard();
+        self.pieces[piece] ^= bb;
+        self.pieces[piece.color()] ^= bb;
+        self.occupied ^= bb;
+        self.empty ^= bb;
+        self.hash ^= KEYS.piece(piece, sq);
+        if matches!(piece, Piece::WPawn | Piece::BPawn) {
+            self.pawn_hash ^= KEYS.piece(piece, sq);
+        }
+
+        let sign = if (self.pieces[piece] & bb).bool() {
+            1
+        } else {
+            -1
+        };
+        self.psqt += eval::psqt(piece, sq) * sign;
+        self.phase += eval::phase(piece) * sign;
+    }
+
+    /// Returns the key of the en-passant square, which is only hashed if
+    /// the side to move can legally capture en passant. Otherwise the
+    /// position is no different from one without an en-passant square, and
+    /// must hash the same for repetitions and transpositions to be found.
+    fn ep_key(&self) -> u64 {
+        let Some(ep) = self.ep else {
+            return 0;
+        };
+        let us = self.side_to_move;
+        let king = self.king_square(us);
+        let mut pawns =
+            ATTACKS.pawn_en_passant(self.pieces[Piece::WPawn.with_color(us)], Some(ep), us);
+        while let Some(from) = pawns.pop_lsb() {
+            if self.is_legal_en_passant(from, ep, king) {
+                return KEYS.ep(Some(ep));
+            }
+        }
+        0
+    }
+
+    /// Computes the Zobrist hash of the position from scratch.
+    fn compute_hash(&self) -> u64 {
+        let mut hash = self.ep_key() ^ KEYS.castling(self.castling_rights);
+        if self.side_to_move == Color::Black {
+            hash ^= KEYS.side();
+        }
+
+        for piece in Piece::iter() {
+            let mut squares = self.pieces[piece];
+            while let Some(sq) = squares.pop_lsb() {
+                hash ^= KEYS.piece(piece, sq);
+            }
+        }
+
+        hash
+    }
+
+    /// Computes the Zobrist hash of the pawns from scratch.
+    fn compute_pawn_hash(&self) -> u64 {
+        let mut hash = 0;
+        for piece in [Piece::WPawn, Piece::BPawn] {
+            let mut squares = self.pieces[piece];
+            while let Some(sq) = squares.pop_lsb() {
+                hash ^= KEYS.piece(piece, sq);
+            }
+        }
+
+        hash
+    }
+
+    /// Computes the piece-square score and game phase of the position
+    /// from scratch.
+    fn compute_psqt(&self) -> (Score, i32) {
+        let mut psqt = Score::default();
+        let mut phase = 0;
+        for piece in Piece::iter() {
+            let mut squares = self.pieces[piece];
+            while let Some(sq) = squares.pop_lsb() {
+                psqt += eval::psqt(piece, sq);
+                phase += eval::phase(piece);
+            }
+        }
+
+        (psqt, phase)
+    }
+
+    /// Returns the square of the piece captured by `mv`, which differs
+    /// from the target square `to` only for en-passant captures.
+    pub(crate) fn capture_square(mv: Move, to: Square) -> Square {
+        if !mv.is_en_passant() {
+            return to;
+        }
+
+        let behind = match to.rank() {
+            Rank::SIX => to as u8 - 8,
+            _ => to as u8 + 8,
+        };
+        Square::from_u8(behind).expect("en-passant captures happen on the board")
+    }
+
+    /// Returns the rook moved by the castling move `mv` landing the king on
+    /// `to`, along with the rook's source and target squares.
+    fn castling_rook(mv: Move, to: Square) -> (Piece, Square, Square) {
+        let rook =
+            Piece::WRook.with_color(mv.moved().expect("move has a valid moved piece").color());
+        match to {
+            Square::G1 => (rook, Square::H1, Square::F1),
+            Square::C1 => (rook, Square::A1, Square::D1),
+            Square::G8 => (rook, Square::H8, Square::F8),
+            Square::C8 => (rook, Square::A8, Square::D8),
+            _ => unreachable!("castling moves always land on the c or g file"),
+        }
+    }
+
     /// Serialize this position to a FEN string.
     fn fen(&self) -> String {
         let mut pieces = String::new();
@@ -334,53 +834,247 @@ impl Position {
         // Remove the trailing slash from the constructed string
         pieces.pop();
 
-        let active_color: String;
-        match self.side_to_move {
-            Color::White => active_color = "w".to_string(),
-            Color::Black => active_color = "b".to_string(),
-        }
+        let active_color: String = self.side_to_move.to_string();
 
         let ep: String = self
             .ep
             .map(|sq| sq.to_string())
             .unwrap_or(String::from("-"));
         let cr: String = self.castling_rights.to_string();
-        let ply: String = self.ply.to_string();
+        let halfmove: String = self.halfmove.to_string();
         let fullmove = self.fullmove.to_string();
 
-        [pieces, active_color, cr, ep, ply, fullmove].join(" ")
+        [pieces, active_color, cr, ep, halfmove, fullmove].join(" ")
     }
 
     /// Deserialize a position from a FEN string.
     fn try_from_fen(fen: &str) -> DiogenesResult<Position> {
-        let fields: Vec<&str> = fen.split(" ").collect();
+        let mut fields = fen.split_whitespace();
+        let mut next = |field| {
+            fields
+                .next()
+                .ok_or(DiogenesError::MissingFenFieldError(field))
+        };
+
+        let placement = next(FenField::Placement)?;
+        let active = next(FenField::ActiveColor)?;
+        let castling = next(FenField::CastlingRights)?;
+        let ep = next(FenField::EnPassant)?;
+        let halfmove =
+            Self::parse_counter(FenField::HalfmoveClock, next(FenField::HalfmoveClock)?)?;
+        let fullmove =
+            Self::parse_counter(FenField::FullmoveNumber, next(FenField::FullmoveNumber)?)?;
+        if let Some(extra) = fields.next() {
+            return Err(DiogenesError::TrailingFenFieldError(extra.to_string()));
+        }
+
+        Self::try_from_fields(placement, active, castling, ep, halfmove, fullmove)
+    }
+
+    /// Builds and validates a [`Position`] from the four board-describing
+    /// FEN fields and the two move counters. EPD records share these fields
+    /// but carry the counters as operations, if at all.
+    pub(crate) fn try_from_fields(
+        placement: &str,
+        active: &str,
+        castling: &str,
+        ep: &str,
+        halfmove: i32,
+        fullmove: i32,
+    ) -> DiogenesResult<Position> {
+        if fullmove == 0 {
+            return Err(DiogenesError::InvalidFenFieldError {
+                field: FenField::FullmoveNumber,
+                value: fullmove.to_string(),
+                reason: "the fullmove number starts at 1".to_string(),
+            });
+        }
 
         // Read pieces from the first component of the FEN
-        let pieces = PieceSet::from_str(fields[0])?;
+        let pieces = PieceSet::from_str(placement)?;
         let occupied = pieces.occupied();
         let empty = pieces.empty();
 
         // Read position metadata
-        let active = Color::from_str(fields[1])?;
-        let castling_rights = CastlingRights::from_str(fields[2])?;
-        let ep_square = match fields[3] {
-            "-" => None,
-            sq => Some(Square::from_str(sq)?),
+        let side_to_move = match active {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => {
+                return Err(DiogenesError::InvalidFenFieldError {
+                    field: FenField::ActiveColor,
+                    value: active.to_string(),
+                    reason: "expected 'w' or 'b'".to_string(),
+                });
+            }
         };
-        let ply: i32 = fields[4].parse::<i32>()?;
-        let fullmove: i32 = fields[5].parse::<i32>()?;
+        let castling_rights = CastlingRights::from_str(castling)?;
+        let ep_square = Self::parse_ep(ep, side_to_move)?;
 
-        Ok(Position {
+        let mut pos = Position {
             empty,
             occupied,
             ep: ep_square,
             castling_rights,
-            side_to_move: active,
+            side_to_move,
             pieces,
-            ply,
+            halfmove,
             fullmove,
             history: VecDeque::new(),
-        })
+            hash: 0,
+            pawn_hash: 0,
+            psqt: Score::default(),
+            phase: 0,
+        };
+        pos.validate(castling, ep)?;
+        pos.hash = pos.compute_hash();
+        pos.pawn_hash = pos.compute_pawn_hash();
+        (pos.psqt, pos.phase) = pos.compute_psqt();
+
+        Ok(pos)
+    }
+
+    /// Parses a non-negative move counter made up of decimal digits only.
+    pub(crate) fn parse_counter(field: FenField, value: &str) -> DiogenesResult<i32> {
+        if let Some((index, ch)) = value.char_indices().find(|(_, ch)| !ch.is_ascii_digit()) {
+            return Err(DiogenesError::InvalidFenCharacterError {
+                field,
+                index,
+                ch,
+                reason: "expected a decimal digit".to_string(),
+            });
+        }
+
+        value
+            .parse::<i32>()
+            .map_err(|err| DiogenesError::InvalidFenFieldError {
+                field,
+                value: value.to_string(),
+                reason: err.to_string(),
+            })
+    }
+
+    /// Parses the en-passant field, which must name a square on the rank
+    /// directly behind a pawn that could just have been pushed two squares.
+    fn parse_ep(ep: &str, side_to_move: Color) -> DiogenesResult<Option<Square>> {
+        if ep == "-" {
+            return Ok(None);
+        }
+
+        let err = |index: usize, ch: char, reason: &str| DiogenesError::InvalidFenCharacterError {
+            field: FenField::EnPassant,
+            index,
+            ch,
+            reason: reason.to_string(),
+        };
+        let mut chars = ep.chars();
+        let (Some(file), Some(rank), None) = (chars.next(), chars.next(), chars.next()) else {
+            return Err(DiogenesError::InvalidFenFieldError {
+                field: FenField::EnPassant,
+                value: ep.to_string(),
+                reason: "expected '-' or a square".to_string(),
+            });
+        };
+        if !('a'..='h').contains(&file) {
+            return Err(err(0, file, "expected a file from 'a' to 'h'"));
+        }
+        match (rank, side_to_move) {
+            ('6', Color::White) | ('3', Color::Black) => Ok(Some(Square::from_str(ep)?)),
+            ('6', Color::Black) | ('3', Color::White) => Err(err(
+                1,
+                rank,
+                "the en passant square is behind the pawn of the side to move",
+            )),
+            _ => Err(err(1, rank, "the en passant square must be on rank 3 or 6")),
+        }
+    }
+
+    /// Checks that the parsed position could arise in a game: each side has
+    /// exactly one king, no pawns stand on the back ranks, the castling rights
+    /// and en-passant square agree with the placement and the side that just
+    /// moved is not in check.
+    ///
+    /// The raw `castling` and `ep` fields are used to point at the offending
+    /// character.
+    fn validate(&self, castling: &str, ep: &str) -> DiogenesResult<()> {
+        let placement_err = |reason: String| DiogenesError::InvalidFenFieldError {
+            field: FenField::Placement,
+            value: self.fen().split(' ').next().unwrap_or_default().to_string(),
+            reason,
+        };
+
+        for color in Color::iter() {
+            let kings = self.pieces[Piece::WKing.with_color(color)].popcount();
+            if kings != 1 {
+                return Err(placement_err(format!(
+                    "{color:?} has {kings} kings, expected exactly 1"
+                )));
+            }
+        }
+
+        let pawns = self.pieces[Piece::WPawn] | self.pieces[Piece::BPawn];
+        if let Some(sq) = (pawns & (board::FIRST_RANK | board::EIGHTH_RANK)).lsb() {
+            return Err(placement_err(format!("pawn on the back rank square {sq}")));
+        }
+
+        let homes = [
+            ('K', Piece::WKing, Square::E1, Piece::WRook, Square::H1),
+            ('Q', Piece::WKing, Square::E1, Piece::WRook, Square::A1),
+            ('k', Piece::BKing, Square::E8, Piece::BRook, Square::H8),
+            ('q', Piece::BKing, Square::E8, Piece::BRook, Square::A8),
+        ];
+        for (ch, king, king_sq, rook, rook_sq) in homes {
+            let Some(index) = castling.find(ch) else {
+                continue;
+            };
+            let missing = if self.piece(king_sq) != Some(king) {
+                Some((king, king_sq))
+            } else if self.piece(rook_sq) != Some(rook) {
+                Some((rook, rook_sq))
+            } else {
+                None
+            };
+            if let Some((piece, sq)) = missing {
+                return Err(DiogenesError::InvalidFenCharacterError {
+                    field: FenField::CastlingRights,
+                    index,
+                    ch,
+                    reason: format!("castling requires {piece} on {sq}"),
+                });
+            }
+        }
+
+        if let Some(sq) = self.ep {
+            // The square the pushed pawn came from, and the square it now stands on.
+            let (origin, pushed) = match self.side_to_move {
+                Color::White => (sq as u8 + 8, sq as u8 - 8),
+                Color::Black => (sq as u8 - 8, sq as u8 + 8),
+            };
+            let origin = Square::from_u8(origin).expect("ep square is on rank 3 or 6");
+            let pushed = Square::from_u8(pushed).expect("ep square is on rank 3 or 6");
+            let pawn = Piece::WPawn.with_color(!self.side_to_move);
+            if self.piece(pushed) != Some(pawn)
+                || self.piece(sq).is_some()
+                || self.piece(origin).is_some()
+            {
+                return Err(DiogenesError::InvalidFenFieldError {
+                    field: FenField::EnPassant,
+                    value: ep.to_string(),
+                    reason: format!(
+                        "no {pawn} could just have been pushed from {origin} to {pushed}"
+                    ),
+                });
+            }
+        }
+
+        let them = !self.side_to_move;
+        if self.is_attacked(self.king_square(them), self.side_to_move) {
+            return Err(DiogenesError::IllegalPositionError(format!(
+                "{them:?} is in check but it is {:?} to move",
+                self.side_to_move
+            )));
+        }
+
+        Ok(())
     }
 }
 
@@ -391,6 +1085,9 @@ mod tests {
     use rstest::rstest;
 
     use crate::bitboard::Bitboard;
+    use crate::color::Color;
+    use crate::error::{DiogenesError, FenField};
+    use crate::r#move::Move;
     use crate::piece::Piece;
     use crate::position::{Position, STARTING_FEN};
     use crate::square::Square;
@@ -480,4 +1177,441 @@ mod tests {
         let s = pos.to_string();
         assert_eq!(s, fen.to_string());
     }
+
+    #[rstest]
+    #[case::empty("", DiogenesError::MissingFenFieldError(FenField::Placement))]
+    #[case::missing_clocks(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+        DiogenesError::MissingFenFieldError(FenField::HalfmoveClock)
+    )]
+    #[case::trailing_field(
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1 extra",
+        DiogenesError::TrailingFenFieldError("extra".into())
+    )]
+    #[case::nine_files(
+        "4k3/8/8/8/8/8/8/4K3P w - - 0 1",
+        DiogenesError::InvalidFenCharacterError {
+            field: FenField::Placement,
+            index: 19,
+            ch: 'P',
+            reason: "rank 1 has more than 8 files".into(),
+        }
+    )]
+    #[case::skip_past_board(
+        "4k3/8/8/8/8/8/8/4K4 w - - 0 1",
+        DiogenesError::InvalidFenCharacterError {
+            field: FenField::Placement,
+            index: 18,
+            ch: '4',
+            reason: "rank 1 has more than 8 files".into(),
+        }
+    )]
+    #[case::consecutive_digits(
+        "4k3/8/8/8/8/8/44/4K3 w - - 0 1",
+        DiogenesError::InvalidFenCharacterError {
+            field: FenField::Placement,
+            index: 15,
+            ch: '4',
+            reason: "empty squares must be counted by a single digit".into(),
+        }
+    )]
+    #[case::invalid_piece(
+        "4k3/8/8/8/3X4/8/8/4K3 w - - 0 1",
+        DiogenesError::InvalidFenCharacterError {
+            field: FenField::Placement,
+            index: 11,
+            ch: 'X',
+            reason: "expected a piece or a digit".into(),
+        }
+    )]
+    #[case::short_rank(
+        "4k3/8/8/8/8/8/8/4K2 w - - 0 1",
+        DiogenesError::InvalidFenFieldError {
+            field: FenField::Placement,
+            value: "4k3/8/8/8/8/8/8/4K2".into(),
+            reason: "rank 1 has 7 files, expected 8".into(),
+        }
+    )]
+    #[case::seven_ranks(
+        "4k3/8/8/8/8/8/4K3 w - - 0 1",
+        DiogenesError::InvalidFenFieldError {
+            field: FenField::Placement,
+            value: "4k3/8/8/8/8/8/4K3".into(),
+            reason: "expected 8 ranks but found 7".into(),
+        }
+    )]
+    #[case::no_white_king(
+        "4k3/8/8/8/8/8/8/8 w - - 0 1",
+        DiogenesError::InvalidFenFieldError {
+            field: FenField::Placement,
+            value: "4k3/8/8/8/8/8/8/8".into(),
+            reason: "White has 0 kings, expected exactly 1".into(),
+        }
+    )]
+    #[case::two_black_kings(
+        "3kk3/8/8/8/8/8/8/4K3 w - - 0 1",
+        DiogenesError::InvalidFenFieldError {
+            field: FenField::Placement,
+            value: "3kk3/8/8/8/8/8/8/4K3".into(),
+            reason: "Black has 2 kings, expected exactly 1".into(),
+        }
+    )]
+    #[case::pawn_on_back_rank(
+        "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+        DiogenesError::InvalidFenFieldError {
+            field: FenField::Placement,
+            value: "4k3/8/8/8/8/8/8/P3K3".into(),
+            reason: "pawn on the back rank square a1".into(),
+        }
+    )]
+    #[case::invalid_color(
+        "4k3/8/8/8/8/8/8/4K3 x - - 0 1",
+        DiogenesError::InvalidFenFieldError {
+            field: FenField::ActiveColor,
+            value: "x".into(),
+            reason: "expected 'w' or 'b'".into(),
+        }
+    )]
+    #[case::castling_without_rook(
+        "r3k2r/8/8/8/8/8/8/R3K3 w KQkq - 0 1",
+        DiogenesError::InvalidFenCharacterError {
+            field: FenField::CastlingRights,
+            index: 0,
+            ch: 'K',
+            reason: "castling requires R on h1".into(),
+        }
+    )]
+    #[case::castling_without_king(
+        "r2k3r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+        DiogenesError::InvalidFenCharacterError {
+            field: FenField::CastlingRights,
+            index: 2,
+            ch: 'k',
+            reason: "castling requires k on e8".into(),
+        }
+    )]
+    #[case::ep_wrong_rank(
+        "4k3/8/8/8/8/8/8/4K3 w - e4 0 1",
+        DiogenesError::InvalidFenCharacterError {
+            field: FenField::EnPassant,
+            index: 1,
+            ch: '4',
+            reason: "the en passant square must be on rank 3 or 6".into(),
+        }
+    )]
+    #[case::ep_wrong_side(
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e3 0 1",
+        DiogenesError::InvalidFenCharacterError {
+            field: FenField::EnPassant,
+            index: 1,
+            ch: '3',
+            reason: "the en passant square is behind the pawn of the side to move".into(),
+        }
+    )]
+    #[case::ep_without_pawn(
+        "4k3/8/8/8/8/8/8/4K3 b - e3 0 1",
+        DiogenesError::InvalidFenFieldError {
+            field: FenField::EnPassant,
+            value: "e3".into(),
+            reason: "no P could just have been pushed from e2 to e4".into(),
+        }
+    )]
+    #[case::negative_halfmove(
+        "4k3/8/8/8/8/8/8/4K3 w - - -1 1",
+        DiogenesError::InvalidFenCharacterError {
+            field: FenField::HalfmoveClock,
+            index: 0,
+            ch: '-',
+            reason: "expected a decimal digit".into(),
+        }
+    )]
+    #[case::zero_fullmove(
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+        DiogenesError::InvalidFenFieldError {
+            field: FenField::FullmoveNumber,
+            value: "0".into(),
+            reason: "the fullmove number starts at 1".into(),
+        }
+    )]
+    #[case::side_not_to_move_in_check(
+        "4k3/8/8/8/4R3/8/8/4K3 w - - 0 1",
+        DiogenesError::IllegalPositionError("Black is in check but it is White to move".into())
+    )]
+    fn test_reject_invalid_fen(#[case] fen: &str, #[case] expected: DiogenesError) {
+        assert_eq!(Position::from_str(fen), Err(expected));
+    }
+
+    #[rstest]
+    #[case::double_push(
+        STARTING_FEN,
+        Move::encode(Square::E2, Square::E4, Move::DOUBLE_PAWN_PUSH, Piece::WPawn, None),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    )]
+    #[case::quiet_black_move(
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        Move::encode(Square::G8, Square::F6, Move::QUIET, Piece::BKnight, None),
+        "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+    )]
+    #[case::kingside_castle(
+        "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 10",
+        Move::encode(Square::E1, Square::G1, Move::KINGSIDE_CASTLE, Piece::WKing, None),
+        "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 4 10"
+    )]
+    #[case::queenside_castle(
+        "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 3 10",
+        Move::encode(Square::E8, Square::C8, Move::QUEENSIDE_CASTLE, Piece::BKing, None),
+        "2kr3r/8/8/8/8/8/8/R3K2R w KQ - 4 11"
+    )]
+    #[case::rook_captures_rook(
+        "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 3 10",
+        Move::encode(
+            Square::H1,
+            Square::H8,
+            Move::CAPTURE,
+            Piece::WRook,
+            Some(Piece::BRook)
+        ),
+        "r3k2R/8/8/8/8/8/8/R3K3 b Qq - 0 10"
+    )]
+    #[case::en_passant(
+        "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        Move::encode(
+            Square::E5,
+            Square::D6,
+            Move::EN_PASSANT,
+            Piece::WPawn,
+            Some(Piece::BPawn)
+        ),
+        "4k3/8/3P4/8/8/8/8/4K3 b - - 0 1"
+    )]
+    #[case::promotion_capture(
+        "1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+        Move::encode(
+            Square::A7,
+            Square::B8,
+            Move::QUEEN_PROMOTION_CAPTURE,
+            Piece::WPawn,
+            Some(Piece::BKnight)
+        ),
+        "1Q2k3/8/8/8/8/8/8/4K3 b - - 0 1"
+    )]
+    fn test_make_move(#[case] fen: &str, #[case] mv: Move, #[case] expected: &str) {
+        let mut pos = Position::from_str(fen).unwrap();
+        let original = pos.clone();
+
+        pos.make_move(mv);
+        assert_eq!(expected, pos.to_string());
+        assert_eq!(Some(mv), pos.last_move());
+
+        assert_eq!(Some(mv), pos.unmake_move());
+        assert_eq!(original, pos, "expected {original:?} but got {pos:?}");
+    }
+
+    #[test]
+    fn test_repetition() {
+        let mut pos = Position::default();
+        let shuffle = [
+            Move::encode(Square::G1, Square::F3, Move::QUIET, Piece::WKnight, None),
+            Move::encode(Square::G8, Square::F6, Move::QUIET, Piece::BKnight, None),
+            Move::encode(Square::F3, Square::G1, Move::QUIET, Piece::WKnight, None),
+            Move::encode(Square::F6, Square::G8, Move::QUIET, Piece::BKnight, None),
+        ];
+        for mv in shuffle {
+            assert!(!pos.is_repetition(0));
+            pos.make_move(mv);
+        }
+        // The starting position counts once it was reached after the root,
+        // but not when it is the root or was reached before it.
+        assert!(pos.is_repetition(5));
+        assert!(pos.is_draw(5));
+        assert!(!pos.is_repetition(4));
+        assert!(!pos.is_repetition(0));
+
+        pos.unmake_move();
+        assert!(!pos.is_draw(5));
+        pos.make_move(shuffle[3]);
+
+        // A threefold repetition is a draw wherever the root is.
+        for mv in shuffle {
+            pos.make_move(mv);
+        }
+        assert!(pos.is_repetition(0));
+        assert!(pos.is_draw(0));
+    }
+
+    #[test]
+    fn test_fifty_move_rule() {
+        let pos = Position::from_str("4k3/8/8/8/8/8/8/4K2R w - - 100 80").unwrap();
+        assert!(pos.is_draw(0));
+        assert!(!pos.is_repetition(0));
+    }
+
+    #[rstest]
+    #[case::starting_position(STARTING_FEN)]
+    #[case::kiwipete("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+    #[case::promotions("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1")]
+    #[case::en_passant("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")]
+    fn test_unmake_move_restores_position(#[case] fen: &str) {
+        let mut pos = Position::from_str(fen).unwrap();
+        let original = pos.clone();
+
+        for mv in original.generate_moves() {
+            pos.make_move(mv);
+            for reply in pos.clone().generate_moves() {
+                let before = pos.clone();
+                pos.make_move(reply);
+                pos.unmake_move();
+                assert_eq!(before, pos, "unmaking {reply:?} after {mv:?}");
+            }
+            pos.unmake_move();
+            assert_eq!(original, pos, "unmaking {mv:?}");
+        }
+    }
+
+    #[test]
+    fn test_null_move() {
+        let mut pos =
+            Position::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+                .unwrap();
+        let original = pos.clone();
+
+        pos.make_null_move();
+        assert_eq!(
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2",
+            pos.to_string()
+        );
+        assert_eq!(Some(Move::NULL), pos.last_move());
+
+        pos.unmake_null_move();
+        assert_eq!(original, pos);
+    }
+
+    #[test]
+    fn test_hash_transposition() {
+        let find_move = |pos: &Position, from: Square, to: Square| {
+            pos.legal_moves()
+                .into_iter()
+                .find(|mv| mv.from() == Some(from) && mv.to() == Some(to))
+                .unwrap()
+        };
+
+        let mut a = Position::default();
+        for (from, to) in [
+            (Square::G1, Square::F3),
+            (Square::G8, Square::F6),
+            (Square::B1, Square::C3),
+        ] {
+            a.make_move(find_move(&a, from, to));
+        }
+
+        let mut b = Position::default();
+        for (from, to) in [
+            (Square::B1, Square::C3),
+            (Square::G8, Square::F6),
+            (Square::G1, Square::F3),
+        ] {
+            b.make_move(find_move(&b, from, to));
+        }
+
+        assert_eq!(a.hash(), b.hash());
+        assert_ne!(a.hash(), Position::default().hash());
+    }
+
+    #[rstest]
+    #[case::capture("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", true)]
+    #[case::no_capturing_pawn("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", false)]
+    #[case::discovered_check("8/8/8/KPp4r/8/8/8/7k w - c6 0 1", false)]
+    fn test_hash_en_passant(#[case] fen: &str, #[case] hashed: bool) {
+        let mut fields: Vec<&str> = fen.split(' ').collect();
+        fields[3] = "-";
+        let without = Position::from_str(&fields.join(" ")).unwrap();
+        let pos = Position::from_str(fen).unwrap();
+        assert_eq!(hashed, pos.hash() != without.hash());
+    }
+
+    #[rstest]
+    #[case::kiwipete("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+    #[case::en_passant("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")]
+    #[case::en_passant_pinned("8/8/8/8/1k3p1R/8/4P3/4K3 w - - 0 1")]
+    fn test_hash_matches_fen(#[case] fen: &str) {
+        let mut pos = Position::from_str(fen).unwrap();
+        for mv in pos.clone().legal_moves() {
+            pos.make_move(mv);
+            let expected = Position::from_str(&pos.to_string()).unwrap().hash();
+            assert_eq!(expected, pos.hash(), "hash after {mv:?}");
+            pos.unmake_move();
+        }
+
+        pos.make_null_move();
+        let expected = Position::from_str(&pos.to_string()).unwrap().hash();
+        assert_eq!(expected, pos.hash(), "hash after null move");
+    }
+
+    #[rstest]
+    #[case::kiwipete("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")]
+    #[case::promotions("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1")]
+    fn test_psqt_matches_fen(#[case] fen: &str) {
+        let mut pos = Position::from_str(fen).unwrap();
+        let original = (pos.psqt(), pos.phase());
+        for mv in pos.clone().legal_moves() {
+            pos.make_move(mv);
+            let expected = Position::from_str(&pos.to_string()).unwrap();
+            assert_eq!(expected.psqt(), pos.psqt(), "psqt after {mv:?}");
+            assert_eq!(expected.phase(), pos.phase(), "phase after {mv:?}");
+            pos.unmake_move();
+            assert_eq!(original, (pos.psqt(), pos.phase()));
+        }
+    }
+
+    #[test]
+    fn test_attackers_to() {
+        let pos = Position::from_str(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+
+        // d5 is hit by the black e6 pawn and both black knights, and the
+        // white e4 pawn and c3 knight.
+        let expected = Square::E6.bitboard()
+            | Square::F6.bitboard()
+            | Square::B6.bitboard()
+            | Square::E4.bitboard()
+            | Square::C3.bitboard();
+        assert_eq!(expected, pos.attackers_to(Square::D5, pos.occupied()));
+
+        // Taking the e2 bishop out of the occupancy lets the f3 queen x-ray
+        // through it to d1.
+        let occupancy = pos.occupied() ^ Square::E2.bitboard();
+        assert!((pos.attackers_to(Square::D1, occupancy) & Square::F3.bitboard()).bool());
+        assert!(!(pos.attackers_to(Square::D1, pos.occupied()) & Square::F3.bitboard()).bool());
+    }
+
+    #[rstest]
+    #[case::attacked_by_pawn(Square::D5, Color::Black, true)]
+    #[case::attacked_by_knight(Square::G4, Color::Black, true)]
+    #[case::attacked_by_queen(Square::H3, Color::White, true)]
+    #[case::not_attacked(Square::A5, Color::White, false)]
+    fn test_is_attacked(#[case] sq: Square, #[case] color: Color, #[case] expected: bool) {
+        let pos = Position::from_str(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        assert_eq!(expected, pos.is_attacked(sq, color));
+    }
+
+    #[rstest]
+    #[case::not_in_check(STARTING_FEN, Bitboard::default())]
+    #[case::single_check(
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        Square::B6.bitboard()
+    )]
+    #[case::double_check(
+        "4k3/8/8/8/8/5n2/8/4K2r w - - 0 1",
+        Square::F3.bitboard() | Square::H1.bitboard()
+    )]
+    fn test_checkers(#[case] fen: &str, #[case] expected: Bitboard) {
+        let pos = Position::from_str(fen).unwrap();
+        assert_eq!(expected, pos.checkers());
+        assert_eq!(expected.bool(), pos.in_check());
+    }
 }
@@ -0,0 +1,189 @@
+//! King safety evaluation.
+//!
+//! A king is safe while it hides behind its own pawns, enemy pawns are not
+//! storming towards it, the files around it are closed and few enemy pieces
+//! bear down on the squares next to it.
+//! See <https://www.chessprogramming.org/King_Safety>
+
+use crate::attacks::ATTACKS;
+use crate::bitboard::Bitboard;
+use crate::color::Color;
+use crate::direction::{Direction, RayDirection};
+use crate::eval::Score;
+use crate::pawns::{adjacent, file_fill};
+use crate::piece::Piece;
+use crate::position::Position;
+
+/// Bonus for each own pawn on the three files around the king, one and two
+/// ranks in front of it respectively.
+const SHIELD: [Score; 2] = [Score::new(15, 0), Score::new(8, 0)];
+
+/// Penalty for each enemy pawn on the three files around the king, two to
+/// four ranks in front of it.
+const STORM: Score = Score::new(-10, 0);
+
+/// Penalty for each file around the king without own pawns, and the extra
+/// penalty if the file has no pawns at all.
+const SEMI_OPEN_FILE: Score = Score::new(-15, 0);
+const OPEN_FILE: Score = Score::new(-10, 0);
+
+/// The attack units contributed by each square of the king zone attacked by
+/// a knight, bishop, rook and queen respectively.
+const ATTACK_WEIGHTS: [i32; 4] = [2, 2, 3, 5];
+
+/// The penalty in centipawns for the number of attack units on the king
+/// zone. It grows slowly at first, since a single attacker is rarely
+/// dangerous, and then steeply as attackers pile up.
+const SAFETY_TABLE: [i32; 100] = [
+    0, 0, 1, 2, 3, 5, 7, 9, 12, 15, 18, 22, 26, 30, 35, 39, 44, 50, 56, 62, 68, 75, 82, 85, 89, 97,
+    105, 113, 122, 131, 140, 150, 169, 180, 191, 202, 213, 225, 237, 248, 260, 272, 283, 295, 307,
+    319, 330, 342, 354, 366, 377, 389, 401, 412, 424, 436, 448, 459, 471, 483, 494, 500, 500, 500,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500,
+];
+
+const NORTH: Direction = Direction::Ray(RayDirection::N);
+
+/// Scores the pawns around the king `king`, with `ours` and `theirs` seen
+/// from White's side of the board. Black's pawns are scored by flipping
+/// every set vertically first.
+fn pawn_cover(king: Bitboard, ours: Bitboard, theirs: Bitboard) -> Score {
+    let mut score = Score::default();
+
+    let near = (king | adjacent(king)) << &NORTH;
+    let far = near << &NORTH;
+    score += SHIELD[0] * (ours & near).popcount();
+    score += SHIELD[1] * (ours & far).popcount();
+
+    let storm = far | (far << &NORTH) | ((far << &NORTH) << &NORTH);
+    score += STORM * (theirs & storm).popcount();
+
+    let mut files = king | adjacent(king);
+    while let Some(sq) = files.pop_lsb() {
+        let file = file_fill(sq.bitboard());
+        if !(ours & file).bool() {
+            score += SEMI_OPEN_FILE;
+            if !(theirs & file).bool() {
+                score += OPEN_FILE;
+            }
+        }
+    }
+
+    score
+}
+
+impl Position {
+    /// Evaluates the safety of both kings from White's point of view.
+    pub fn king_safety(&self) -> Score {
+        self.king_safety_of(Color::White) - self.king_safety_of(Color::Black)
+    }
+
+    /// Evaluates the safety of the king of `color` from its own point of
+    /// view, so that an exposed king scores negatively.
+    fn king_safety_of(&self, color: Color) -> Score {
+        let king = self.king_square(color);
+        let ours = self.pieces()[Piece::WPawn.with_color(color)];
+        let theirs = self.pieces()[Piece::WPawn.with_color(!color)];
+
+        let mut score = match color {
+            Color::White => pawn_cover(king.bitboard(), ours, theirs),
+            Color::Black => pawn_cover(
+                king.bitboard().flip_vertical(),
+                ours.flip_vertical(),
+                theirs.flip_vertical(),
+            ),
+        };
+
+        let zone = ATTACKS.king(king, Bitboard::default()) | king.bitboard();
+        let occupied = self.occupied();
+        let mut attackers = 0;
+        let mut units = 0;
+        for (kind, weight) in [Piece::WKnight, Piece::WBishop, Piece::WRook, Piece::WQueen]
+            .into_iter()
+            .zip(ATTACK_WEIGHTS)
+        {
+            let piece = kind.with_color(!color);
+            let mut squares = self.pieces()[piece];
+            while let Some(sq) = squares.pop_lsb() {
+                let attacks = match kind {
+                    Piece::WKnight => ATTACKS.knight(sq, Bitboard::default()),
+                    Piece::WBishop => ATTACKS.bishop(sq, occupied),
+                    Piece::WRook => ATTACKS.rook(sq, occupied),
+                    _ => ATTACKS.queen(sq, occupied),
+                };
+                let hits = (attacks & zone).popcount();
+                if hits > 0 {
+                    attackers += 1;
+                    units += weight * hits;
+                }
+            }
+        }
+
+        // A lone attacker cannot mate without help.
+        if attackers >= 2 {
+            let penalty = SAFETY_TABLE[units.min(99) as usize];
+            score -= Score::new(penalty, penalty / 4);
+        }
+
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use crate::eval::Score;
+    use crate::position::Position;
+
+    fn king_safety(fen: &str) -> Score {
+        Position::from_str(fen).unwrap().king_safety()
+    }
+
+    #[rstest]
+    #[case::starting_position("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")]
+    #[case::castled("rnbq1rk1/ppppbppp/5n2/4p3/4P3/5N2/PPPPBPPP/RNBQ1RK1 w - - 6 5")]
+    fn test_symmetric_positions_are_equal(#[case] fen: &str) {
+        assert_eq!(Score::default(), king_safety(fen));
+    }
+
+    #[rstest]
+    #[case::kiwipete(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1"
+    )]
+    #[case::attack(
+        "6k1/5ppp/8/8/8/5q2/5PPP/3r2K1 w - - 0 1",
+        "3R2k1/5ppp/5Q2/8/8/8/5PPP/6K1 b - - 0 1"
+    )]
+    fn test_mirrored_positions_are_opposite(#[case] fen: &str, #[case] mirrored: &str) {
+        assert_eq!(king_safety(fen), -king_safety(mirrored));
+    }
+
+    #[rstest]
+    #[case::pawn_shield(
+        "6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1",
+        "6k1/5ppp/8/8/8/8/8/6K1 w - - 0 1"
+    )]
+    #[case::advanced_shield(
+        "6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1",
+        "6k1/5ppp/8/8/5PPP/8/8/6K1 w - - 0 1"
+    )]
+    #[case::pawn_storm(
+        "6k1/8/8/8/8/8/5PPP/6K1 w - - 0 1",
+        "6k1/8/8/8/5ppp/8/5PPP/6K1 w - - 0 1"
+    )]
+    #[case::open_file(
+        "6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1",
+        "6k1/5ppp/8/8/8/8/5P1P/6K1 w - - 0 1"
+    )]
+    #[case::second_attacker(
+        "6k1/5ppp/8/8/8/8/5PPP/3r2K1 w - - 0 1",
+        "6k1/5ppp/8/8/8/5q2/5PPP/3r2K1 w - - 0 1"
+    )]
+    fn test_safer(#[case] safe: &str, #[case] exposed: &str) {
+        assert!(king_safety(safe).mg > king_safety(exposed).mg);
+    }
+}
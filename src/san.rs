@@ -0,0 +1,285 @@
+//! Standard Algebraic Notation (SAN), the notation used by PGN and by
+//! humans, e.g. `Nbd7`, `exd5`, `e8=Q+` and `O-O-O#`.
+//! See <https://www.chessprogramming.org/Algebraic_Chess_Notation#Standard_Algebraic_Notation_.28SAN.29>
+
+use std::str::FromStr;
+
+use crate::color::Color;
+use crate::error::{DiogenesError, DiogenesResult};
+use crate::r#move::Move;
+use crate::piece::Piece;
+use crate::position::Position;
+use crate::square::Square;
+
+/// Characters which may trail a move in SAN: check and mate markers and
+/// move quality annotations such as `!?`.
+const SUFFIXES: &[char] = &['+', '#', '!', '?'];
+
+/// Returns the piece type written as `letter` in SAN, as a white piece.
+fn piece_kind(letter: char) -> Option<Piece> {
+    match letter.to_ascii_uppercase() {
+        'N' => Some(Piece::WKnight),
+        'B' => Some(Piece::WBishop),
+        'R' => Some(Piece::WRook),
+        'Q' => Some(Piece::WQueen),
+        'K' => Some(Piece::WKing),
+        _ => None,
+    }
+}
+
+/// Returns the SAN letter of `piece`, which is always uppercase.
+fn piece_letter(piece: Piece) -> String {
+    piece.to_string().to_uppercase()
+}
+
+fn invalid(san: &str, reason: &str) -> DiogenesError {
+    DiogenesError::InvalidSanError {
+        san: san.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+impl Position {
+    /// Formats the legal move `mv` in Standard Algebraic Notation, including
+    /// a `+` or `#` suffix if it gives check or mate.
+    pub fn to_san(&self, mv: Move) -> String {
+        let from = mv.from().expect("move has a valid source square");
+        let to = mv.to().expect("move has a valid target square");
+        let moved = mv.moved().expect("move has a valid moved piece");
+
+        let mut san = if mv.is_castle() {
+            match mv.flags() {
+                Move::KINGSIDE_CASTLE => "O-O".to_string(),
+                _ => "O-O-O".to_string(),
+            }
+        } else if moved.with_color(Color::White) == Piece::WPawn {
+            let mut san = String::new();
+            if mv.capture() {
+                san.push_str(&from.to_string()[..1]);
+                san.push('x');
+            }
+            san.push_str(&to.to_string());
+            if let Some(promoted) = mv.promoted() {
+                san.push('=');
+                san.push_str(&piece_letter(promoted));
+            }
+            san
+        } else {
+            let mut san = piece_letter(moved);
+            san.push_str(&self.disambiguation(mv));
+            if mv.capture() {
+                san.push('x');
+            }
+            san.push_str(&to.to_string());
+            san
+        };
+
+        let mut pos = self.clone();
+        pos.make_move(mv);
+        if pos.in_check() {
+            san.push(if pos.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+
+        san
+    }
+
+    /// Returns the source file, rank or square needed to tell `mv` apart
+    /// from other legal moves of the same piece type to the same square.
+    fn disambiguation(&self, mv: Move) -> String {
+        let from = mv.from().expect("move has a valid source square");
+        let others: Vec<Square> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|other| {
+                other.moved() == mv.moved() && other.to() == mv.to() && other.from() != mv.from()
+            })
+            .filter_map(|other| other.from())
+            .collect();
+
+        let square = from.to_string();
+        if others.is_empty() {
+            String::new()
+        } else if others.iter().all(|sq| sq.file() as u8 != from.file() as u8) {
+            square[..1].to_string()
+        } else if others.iter().all(|sq| sq.rank() as u8 != from.rank() as u8) {
+            square[1..].to_string()
+        } else {
+            square
+        }
+    }
+
+    /// Parses a move in Standard Algebraic Notation and resolves it against
+    /// the legal moves of the position.
+    ///
+    /// Common variations are accepted as well: castling written with zeros
+    /// (`0-0`), a missing capture marker (`Nd5` for `Nxd5`), missing or
+    /// superfluous check markers, annotations like `!?` and promotions
+    /// without `=` (`e8Q`).
+    pub fn parse_san(&self, san: &str) -> DiogenesResult<Move> {
+        let text = san.trim().trim_end_matches(SUFFIXES);
+        if text.is_empty() {
+            return Err(invalid(san, "empty move"));
+        }
+
+        if let Some(flag) = match text {
+            "O-O" | "0-0" => Some(Move::KINGSIDE_CASTLE),
+            "O-O-O" | "0-0-0" => Some(Move::QUEENSIDE_CASTLE),
+            _ => None,
+        } {
+            return self
+                .legal_moves()
+                .into_iter()
+                .find(|mv| mv.flags() == flag)
+                .ok_or_else(|| invalid(san, "castling is not legal"));
+        }
+
+        let (kind, rest) = match text.chars().next().and_then(piece_kind) {
+            // A lowercase 'b' is a pawn on the b-file, never a bishop.
+            Some(kind) if text.starts_with(|c: char| c.is_ascii_uppercase()) => (kind, &text[1..]),
+            _ => (Piece::WPawn, text),
+        };
+
+        let (rest, promotion) = match rest.char_indices().last() {
+            Some((i, letter)) if kind == Piece::WPawn && !letter.is_ascii_digit() => {
+                let kind = piece_kind(letter)
+                    .filter(|kind| *kind != Piece::WKing)
+                    .ok_or_else(|| invalid(san, "expected a promotion piece"))?;
+                (rest[..i].trim_end_matches('='), Some(kind))
+            }
+            _ => (rest, None),
+        };
+
+        if rest.len() < 2 || !rest.is_char_boundary(rest.len() - 2) {
+            return Err(invalid(san, "expected a target square"));
+        }
+        let (disambiguation, target) = rest.split_at(rest.len() - 2);
+        let to = Square::from_str(target).map_err(|_| invalid(san, "expected a target square"))?;
+
+        let mut file = None;
+        let mut rank = None;
+        for ch in disambiguation.chars().filter(|ch| !matches!(ch, 'x' | ':')) {
+            match ch {
+                'a'..='h' => file = Some(ch as u8 - b'a'),
+                '1'..='8' => rank = Some(ch as u8 - b'1'),
+                _ => return Err(invalid(san, &format!("unexpected character {ch:?}"))),
+            }
+        }
+
+        let side = self.side_to_move();
+        let candidates: Vec<Move> = self
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| {
+                let from = mv.from().expect("move has a valid source square");
+                !mv.is_castle()
+                    && mv.moved() == Some(kind.with_color(side))
+                    && mv.to() == Some(to)
+                    && mv.promoted() == promotion.map(|kind| kind.with_color(side))
+                    && file.is_none_or(|file| from.file() as u8 == file)
+                    && rank.is_none_or(|rank| from.rank() as u8 == rank)
+            })
+            .collect();
+
+        match candidates[..] {
+            [mv] => Ok(mv),
+            [] => Err(invalid(san, "no legal move matches")),
+            _ => Err(invalid(san, "ambiguous move")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use crate::error::DiogenesError;
+    use crate::position::Position;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    #[rstest]
+    #[case::pawn_push(Position::default().to_string(), "e2e4", "e4")]
+    #[case::knight(Position::default().to_string(), "g1f3", "Nf3")]
+    #[case::pawn_capture(KIWIPETE, "d5e6", "dxe6")]
+    #[case::piece_capture(KIWIPETE, "e5f7", "Nxf7")]
+    #[case::kingside_castle(KIWIPETE, "e1g1", "O-O")]
+    #[case::queenside_castle(KIWIPETE, "e1c1", "O-O-O")]
+    #[case::en_passant("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", "exd6")]
+    #[case::promotion("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q", "a8=Q+")]
+    #[case::underpromotion_capture("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8n", "axb8=N")]
+    #[case::file_disambiguation("4k3/8/8/8/8/8/4K3/R6R w - - 0 1", "a1d1", "Rad1")]
+    #[case::rank_disambiguation("R7/7k/8/8/8/8/8/R3K3 w - - 0 1", "a1a4", "R1a4")]
+    #[case::square_disambiguation("7k/8/8/8/Q1Q5/8/Q7/4K3 w - - 0 1", "a4b3", "Qa4b3")]
+    #[case::pinned_piece_needs_no_disambiguation(
+        "k3r3/8/8/8/8/8/4N3/2N1K3 w - - 0 1",
+        "c1d3",
+        "Nd3"
+    )]
+    #[case::check("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8", "Ra8+")]
+    #[case::mate("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", "Ra8#")]
+    #[case::discovered_check("4k3/8/8/8/8/8/4N3/4R1K1 w - - 0 1", "e2c3", "Nc3+")]
+    fn test_san(#[case] fen: impl AsRef<str>, #[case] lan: &str, #[case] san: &str) {
+        let pos = Position::from_str(fen.as_ref()).unwrap();
+        let mv = pos.parse_uci_move(lan).unwrap();
+        assert_eq!(san, pos.to_san(mv));
+        assert_eq!(Ok(mv), pos.parse_san(san));
+    }
+
+    #[rstest]
+    #[case::zero_castling(KIWIPETE, "0-0", "e1g1")]
+    #[case::zero_long_castling(KIWIPETE, "0-0-0", "e1c1")]
+    #[case::missing_capture(KIWIPETE, "Nf7", "e5f7")]
+    #[case::missing_pawn_capture(KIWIPETE, "de6", "d5e6")]
+    #[case::annotation(KIWIPETE, "Nxf7!?", "e5f7")]
+    #[case::superfluous_check(KIWIPETE, "Qxf6+", "f3f6")]
+    #[case::promotion_without_equals("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8Q", "a7a8q")]
+    #[case::lowercase_promotion("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=n", "a7a8n")]
+    #[case::bishop_and_b_pawn("4k3/8/8/8/8/2p5/1P1B4/4K3 w - - 0 1", "Bxc3", "d2c3")]
+    #[case::b_pawn_and_bishop("4k3/8/8/8/8/2p5/1P1B4/4K3 w - - 0 1", "bxc3", "b2c3")]
+    #[case::over_disambiguated(KIWIPETE, "Ne5xf7", "e5f7")]
+    fn test_parse_san_variants(#[case] fen: &str, #[case] san: &str, #[case] lan: &str) {
+        let pos = Position::from_str(fen).unwrap();
+        assert_eq!(pos.parse_uci_move(lan), pos.parse_san(san));
+    }
+
+    #[rstest]
+    #[case::empty("", "empty move")]
+    #[case::illegal("e5", "no legal move matches")]
+    #[case::ambiguous("Nd2", "ambiguous move")]
+    #[case::castling("O-O", "castling is not legal")]
+    #[case::bad_square("Nz9", "expected a target square")]
+    #[case::bad_character("N*f3", "unexpected character '*'")]
+    #[case::missing_promotion("a8", "no legal move matches")]
+    #[case::king_promotion("a8=K", "expected a promotion piece")]
+    fn test_parse_invalid_san(#[case] san: &str, #[case] reason: &str) {
+        let pos = Position::from_str("4k3/P7/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        assert_eq!(
+            Err(DiogenesError::InvalidSanError {
+                san: san.to_string(),
+                reason: reason.to_string(),
+            }),
+            pos.parse_san(san)
+        );
+    }
+
+    #[test]
+    fn test_san_round_trip() {
+        let mut pos = Position::from_str(KIWIPETE).unwrap();
+        for mv in pos.clone().legal_moves() {
+            let san = pos.to_san(mv);
+            assert_eq!(Ok(mv), pos.parse_san(&san), "{san}");
+
+            pos.make_move(mv);
+            for reply in pos.legal_moves() {
+                assert_eq!(Ok(reply), pos.parse_san(&pos.to_san(reply)));
+            }
+            pos.unmake_move();
+        }
+    }
+}
@@ -0,0 +1,714 @@
+use std::cmp::Reverse;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::r#move::Move;
+use crate::pawns::PawnTable;
+use crate::position::Position;
+use crate::timeman::TimeManager;
+use crate::tt::{Bound, TranspositionTable};
+
+/// The deepest the search will ever look, in plies from the root.
+pub const MAX_PLY: usize = 128;
+
+/// A score larger than any the search can return, used as the initial window.
+pub const INFINITY: i32 = 32_000;
+
+/// The score of delivering checkmate at the root. Mates further away score
+/// one point less per ply so that the search prefers the quickest mate.
+pub const MATE: i32 = 31_000;
+
+/// The margin added to the value of a captured piece in quiescence search
+/// before deciding that the capture cannot possibly raise alpha, covering
+/// positional gains the material balance does not show.
+const DELTA_MARGIN: i32 = 200;
+
+/// The ordering bonus that puts captures which do not lose material ahead
+/// of quiet moves, and the penalty that puts losing captures behind them.
+const GOOD_CAPTURE: i32 = 1 << 16;
+
+/// Any score beyond this bound is a forced mate found within [`MAX_PLY`].
+pub const MATE_BOUND: i32 = MATE - MAX_PLY as i32;
+
+/// Returns the number of moves (not plies) until mate if `score` is a mate
+/// score, negative if the side to move is getting mated.
+pub fn mate_in(score: i32) -> Option<i32> {
+    if score > MATE_BOUND {
+        Some((MATE - score + 1) / 2)
+    } else if score < -MATE_BOUND {
+        Some(-(MATE + score) / 2)
+    } else {
+        None
+    }
+}
+
+/// Conditions under which the search stops. An empty set of limits searches
+/// until [`MAX_PLY`] or until it is stopped externally.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum depth to iterate to, in plies.
+    pub depth: Option<u32>,
+
+    /// The maximum number of nodes to visit.
+    pub nodes: Option<u64>,
+
+    /// The deadlines to search within.
+    pub time: Option<TimeManager>,
+
+    /// Stop as soon as a mate in at most this many moves is found.
+    pub mate: Option<u32>,
+
+    /// Only search these moves at the root. Every legal move is searched
+    /// when this is empty.
+    pub root_moves: Vec<Move>,
+}
+
+/// The result of a single iteration of iterative deepening.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Iteration {
+    /// The depth that was searched, in plies.
+    pub depth: u32,
+
+    /// The score of the position in centipawns from the point of view of
+    /// the side to move, or a mate score as described by [`mate_in`].
+    pub score: i32,
+
+    /// The number of nodes visited since the search started.
+    pub nodes: u64,
+
+    /// The time elapsed since the search started.
+    pub time: Duration,
+
+    /// The principal variation, i.e. the line of best play found.
+    pub pv: Vec<Move>,
+}
+
+impl Iteration {
+    /// Returns the move the search recommends playing, if there is any legal move.
+    pub fn best_move(&self) -> Option<Move> {
+        self.pv.first().copied()
+    }
+}
+
+/// Runs an iterative-deepening principal variation search over a [`Position`].
+///
+/// See <https://www.chessprogramming.org/Principal_Variation_Search>
+pub struct Searcher {
+    limits: Limits,
+    stop: Arc<AtomicBool>,
+    start: Instant,
+    nodes: u64,
+    stopped: bool,
+    tt: Arc<TranspositionTable>,
+    pawns: PawnTable,
+}
+
+impl Searcher {
+    /// Creates a searcher that obeys `limits`, gives up as soon as `stop`
+    /// is set from another thread and shares results through `tt`.
+    pub fn new(limits: Limits, stop: Arc<AtomicBool>, tt: Arc<TranspositionTable>) -> Self {
+        Self {
+            limits,
+            stop,
+            tt,
+            pawns: PawnTable::default(),
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+        }
+    }
+
+    /// Searches `pos` with iterative deepening until a limit is reached,
+    /// calling `report` with every completed iteration, and returns the
+    /// result of the deepest one.
+    ///
+    /// An interrupted iteration still contributes its best move as long as
+    /// at least one root move was fully searched, because the previous best
+    /// move is always searched first. `pos` is restored before returning.
+    pub fn search(&mut self, pos: &mut Position, mut report: impl FnMut(&Iteration)) -> Iteration {
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.stopped = false;
+        self.tt.new_search();
+
+        let mut root_moves = pos.legal_moves();
+        if !self.limits.root_moves.is_empty() {
+            root_moves.retain(|mv| self.limits.root_moves.contains(mv));
+        }
+        Self::order_moves(pos, &mut root_moves);
+
+        let mut best = Iteration {
+            depth: 0,
+            score: 0,
+            nodes: 0,
+            time: Duration::ZERO,
+            pv: root_moves.first().copied().into_iter().collect(),
+        };
+        if root_moves.is_empty() {
+            best.score = if pos.in_check() { -MATE } else { 0 };
+            return best;
+        }
+
+        let max_depth = self
+            .limits
+            .depth
+            .unwrap_or(MAX_PLY as u32)
+            .clamp(1, MAX_PLY as u32);
+        let mut pv = Vec::new();
+        for depth in 1..=max_depth {
+            let score = self.search_moves(pos, &root_moves, depth, -INFINITY, INFINITY, 0, &mut pv);
+            if self.stopped {
+                if !pv.is_empty() {
+                    best.score = score;
+                    best.pv = pv;
+                }
+                break;
+            }
+
+            best = Iteration {
+                depth,
+                score,
+                nodes: self.nodes,
+                time: self.start.elapsed(),
+                pv: pv.clone(),
+            };
+            report(&best);
+
+            // Search the best move of this iteration first in the next one.
+            if let Some(i) = root_moves
+                .iter()
+                .position(|&mv| Some(mv) == best.best_move())
+            {
+                root_moves[..=i].rotate_right(1);
+            }
+
+            if let Some(time) = &mut self.limits.time {
+                time.update(&best);
+                if time.soft_expired(best.time) {
+                    break;
+                }
+            }
+
+            let mate = mate_in(score);
+            let found_mate = self
+                .limits
+                .mate
+                .is_some_and(|limit| mate.is_some_and(|m| m > 0 && m <= limit as i32));
+            // Deeper iterations cannot change a mate score found within the horizon.
+            let resolved = mate.is_some_and(|m| m.unsigned_abs() * 2 <= depth);
+            if found_mate || resolved {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// Returns the number of nodes visited by the current search.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    /// Negamax alpha-beta search of `pos` to `depth` plies, storing the
+    /// principal variation in `pv`.
+    fn negamax(
+        &mut self,
+        pos: &mut Position,
+        depth: u32,
+        alpha: i32,
+        beta: i32,
+        ply: usize,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        pv.clear();
+        if self.should_stop() {
+            return 0;
+        }
+        if pos.is_draw(ply) {
+            return 0;
+        }
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiescence(pos, alpha, beta, ply, pv);
+        }
+        self.nodes += 1;
+
+        // Outside of the principal variation, where the window is null, a
+        // deep enough result from the table can be trusted as it is.
+        let entry = self.tt.probe(pos.hash(), ply);
+        if let Some(entry) = entry
+            && beta - alpha == 1
+            && entry.depth >= depth
+        {
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.score >= beta,
+                Bound::Upper => entry.score <= alpha,
+            };
+            if cutoff {
+                return entry.score;
+            }
+        }
+
+        let mut moves = pos.legal_moves();
+        if moves.is_empty() {
+            return if pos.in_check() {
+                -MATE + ply as i32
+            } else {
+                0
+            };
+        }
+        Self::order_moves(pos, &mut moves);
+        if let Some(i) = entry.and_then(|entry| moves.iter().position(|&mv| entry.is_best_move(mv)))
+        {
+            moves[..=i].rotate_right(1);
+        }
+
+        self.search_moves(pos, &moves, depth, alpha, beta, ply, pv)
+    }
+
+    /// Extends a leaf of the main search with captures and queen promotions
+    /// until the position is quiet, so that it is never scored in the middle
+    /// of an exchange. Every evasion is searched when in check.
+    ///
+    /// The side to move may decline every capture, so the static evaluation
+    /// ("stand pat") is a lower bound on the score when not in check.
+    ///
+    /// See <https://www.chessprogramming.org/Quiescence_Search>
+    fn quiescence(
+        &mut self,
+        pos: &mut Position,
+        mut alpha: i32,
+        beta: i32,
+        ply: usize,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        pv.clear();
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+
+        let in_check = pos.in_check();
+        if ply >= MAX_PLY {
+            return if in_check {
+                0
+            } else {
+                pos.evaluate(&mut self.pawns)
+            };
+        }
+
+        let (mut best, stand_pat, mut moves) = if in_check {
+            let moves = pos.legal_moves();
+            if moves.is_empty() {
+                return -MATE + ply as i32;
+            }
+            (-INFINITY, -INFINITY, moves)
+        } else {
+            let stand_pat = pos.evaluate(&mut self.pawns);
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+            (stand_pat, stand_pat, pos.legal_captures())
+        };
+        Self::order_moves(pos, &mut moves);
+
+        let mut child_pv = Vec::new();
+        for mv in moves {
+            // Delta pruning: skip captures that cannot raise alpha even if
+            // the captured piece came for free with a margin to spare.
+            let gain = mv.captured().map_or(0, |piece| pos.piece_value(piece));
+            if !in_check && mv.promoted().is_none() && stand_pat + gain + DELTA_MARGIN <= alpha {
+                continue;
+            }
+            // Captures that lose material in the exchange are almost never
+            // better than standing pat.
+            if !in_check && !pos.see_ge(mv, 0) {
+                continue;
+            }
+
+            pos.make_move(mv);
+            let score = -self.quiescence(pos, -beta, -alpha, ply + 1, &mut child_pv);
+            pos.unmake_move();
+
+            if self.stopped {
+                break;
+            }
+            if score > best {
+                best = score;
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(mv);
+                pv.extend_from_slice(&child_pv);
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Searches each of `moves` in turn, the first with the full window and
+    /// the rest with a null window that is only widened when a move turns
+    /// out to beat the best one so far. Returns the best (fail-soft) score.
+    #[allow(clippy::too_many_arguments)]
+    fn search_moves(
+        &mut self,
+        pos: &mut Position,
+        moves: &[Move],
+        depth: u32,
+        mut alpha: i32,
+        beta: i32,
+        ply: usize,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        pv.clear();
+        let original_alpha = alpha;
+        let mut best = -INFINITY;
+        let mut best_move = None;
+        let mut child_pv = Vec::new();
+        for (i, &mv) in moves.iter().enumerate() {
+            pos.make_move(mv);
+            let mut score = if i == 0 {
+                -self.negamax(pos, depth - 1, -beta, -alpha, ply + 1, &mut child_pv)
+            } else {
+                -self.negamax(pos, depth - 1, -alpha - 1, -alpha, ply + 1, &mut child_pv)
+            };
+            if i > 0 && score > alpha && score < beta {
+                score = -self.negamax(pos, depth - 1, -beta, -alpha, ply + 1, &mut child_pv);
+            }
+            pos.unmake_move();
+
+            if self.stopped {
+                break;
+            }
+            if score > best {
+                best = score;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv);
+                pv.clear();
+                pv.push(mv);
+                pv.extend_from_slice(&child_pv);
+                if alpha >= beta {
+                    break;
+                }
+            }
+        }
+
+        // A root search restricted to some of the moves says nothing about
+        // the position itself, which a later search may well reach again.
+        let restricted = ply == 0 && !self.limits.root_moves.is_empty();
+        if !self.stopped && !restricted {
+            let bound = if best >= beta {
+                Bound::Lower
+            } else if best > original_alpha {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            };
+            self.tt
+                .store(pos.hash(), depth, bound, best, ply, best_move);
+        }
+
+        best
+    }
+
+    /// Orders `moves` so that the most promising are searched first:
+    /// promotions and captures that do not lose material, most valuable
+    /// victim by least valuable attacker (MVV-LVA) first, then quiet moves
+    /// and finally the captures that [`Position::see`] judges to lose material.
+    fn order_moves(pos: &Position, moves: &mut [Move]) {
+        moves.sort_by_cached_key(|&mv| {
+            let value = |piece| pos.piece_value(piece);
+            let mut score = mv.promoted().map_or(0, value);
+            if let Some(captured) = mv.captured() {
+                let attacker = mv.moved().map_or(0, value);
+                let mvv_lva = 10 * value(captured) - attacker;
+                score += match pos.see_ge(mv, 0) {
+                    true => GOOD_CAPTURE + mvv_lva,
+                    false => -GOOD_CAPTURE + mvv_lva,
+                };
+            }
+            Reverse(score)
+        });
+    }
+
+    /// Returns `true` once the search has to stop: the stop flag is set,
+    /// or the node limit or hard deadline has been reached. The clock is only read
+    /// every 1024 nodes to keep the check cheap.
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        self.stopped = self.stop.load(Ordering::Relaxed)
+            || self.limits.nodes.is_some_and(|nodes| self.nodes >= nodes)
+            || (self.nodes.is_multiple_of(1024)
+                && self
+                    .limits
+                    .time
+                    .as_ref()
+                    .is_some_and(|time| self.start.elapsed() >= time.hard_limit()));
+        self.stopped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::time::{Duration, Instant};
+
+    use rstest::rstest;
+
+    use crate::pawns::PawnTable;
+    use crate::position::Position;
+    use crate::search::{INFINITY, Iteration, Limits, MATE, Searcher, mate_in};
+    use crate::timeman::TimeManager;
+    use crate::tt::TranspositionTable;
+
+    fn search(fen: &str, limits: Limits) -> (Iteration, Vec<Iteration>) {
+        let mut pos = Position::from_str(fen).unwrap();
+        let mut iterations = Vec::new();
+        let mut searcher = Searcher::new(limits, Arc::new(AtomicBool::new(false)), Arc::default());
+        let best = searcher.search(&mut pos, |it| iterations.push(it.clone()));
+        assert_eq!(fen, pos.to_string(), "search must restore the position");
+        (best, iterations)
+    }
+
+    fn depth(depth: u32) -> Limits {
+        Limits {
+            depth: Some(depth),
+            ..Limits::default()
+        }
+    }
+
+    #[rstest]
+    #[case::mate(MATE - 1, Some(1))]
+    #[case::mate_in_two(MATE - 3, Some(2))]
+    #[case::mated(-MATE, Some(0))]
+    #[case::mated_in_one(-MATE + 2, Some(-1))]
+    #[case::not_mate(250, None)]
+    fn test_mate_in(#[case] score: i32, #[case] expected: Option<i32>) {
+        assert_eq!(expected, mate_in(score));
+    }
+
+    #[rstest]
+    #[case::back_rank("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", 1)]
+    #[case::smothered("6rk/6pp/7N/8/8/8/8/6K1 w - - 0 1", "h6f7", 1)]
+    #[case::mate_in_two(
+        "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1",
+        "d5f6",
+        2
+    )]
+    fn test_finds_mate(#[case] fen: &str, #[case] expected: &str, #[case] moves: i32) {
+        let (best, _) = search(fen, depth(4));
+        let mv = best.best_move().unwrap();
+        assert_eq!(expected, mv.to_string());
+        assert_eq!(Some(moves), mate_in(best.score));
+    }
+
+    #[test]
+    fn test_captures_hanging_queen() {
+        let (best, _) = search("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", depth(3));
+        let mv = best.best_move().unwrap();
+        assert_eq!("d2d5", mv.to_string());
+        assert!(best.score > 0);
+    }
+
+    #[test]
+    fn test_order_moves() {
+        // Rxd5 trades rooks, Qxe6 gives up the queen for a pawn.
+        let pos = Position::from_str("4k3/5p2/4p3/3r4/8/8/3R4/4Q1K1 w - - 0 1").unwrap();
+        let mut moves = pos.legal_moves();
+        Searcher::order_moves(&pos, &mut moves);
+
+        let text: Vec<String> = moves.iter().map(|mv| mv.to_string()).collect();
+        assert_eq!("d2d5", text[0]);
+        assert_eq!("e1e6", text[text.len() - 1]);
+    }
+
+    #[test]
+    fn test_quiescence_avoids_defended_pawn() {
+        // Without quiescence a one-ply search sees a free pawn, not the recapture.
+        let (best, _) = search("4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1", depth(1));
+        let mv = best.best_move().unwrap();
+        assert_ne!("d1d5", mv.to_string());
+        assert!(best.score >= 700, "score {}", best.score);
+    }
+
+    /// Runs quiescence search on `fen`, checking that the position is left untouched.
+    fn quiescence(fen: &str) -> i32 {
+        let mut pos = Position::from_str(fen).unwrap();
+        let mut searcher = Searcher::new(
+            Limits::default(),
+            Arc::new(AtomicBool::new(false)),
+            Arc::default(),
+        );
+        let score = searcher.quiescence(&mut pos, -INFINITY, INFINITY, 0, &mut Vec::new());
+        assert_eq!(fen, pos.to_string());
+        score
+    }
+
+    /// The quiescence score is the static evaluation of the position the
+    /// captures settle in, given here with the original side to move.
+    #[rstest]
+    #[case::quiet("4k3/8/8/8/8/8/8/3QK3 w - - 0 1", "4k3/8/8/8/8/8/8/3QK3 w - - 0 1")]
+    #[case::hanging_queen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", "4k3/8/8/3R4/8/8/8/4K3 w - - 0 1")]
+    #[case::defended_pawn(
+        "4k3/8/4p3/3p4/8/8/8/3QK3 b - - 0 1",
+        "4k3/8/4p3/3p4/8/8/8/3QK3 b - - 0 1"
+    )]
+    #[case::exchange_sequence(
+        "4k3/3r4/8/3q4/8/8/8/3RK3 w - - 0 1",
+        "4k3/8/8/3r4/8/8/8/4K3 w - - 0 1"
+    )]
+    // After Rxd5, trading rooks on d5 would leave the material balance
+    // where it is, so only mobility tells the two lines apart, and it
+    // scores the position with all three rooks on the board better for Black.
+    #[case::recapture_declined(
+        "4k3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1",
+        "4k3/3r4/8/3R4/8/8/8/3RK3 w - - 0 1"
+    )]
+    fn test_quiescence(#[case] fen: &str, #[case] settled: &str) {
+        let expected = Position::from_str(settled)
+            .unwrap()
+            .evaluate(&mut PawnTable::default());
+        assert_eq!(expected, quiescence(fen));
+    }
+
+    #[test]
+    fn test_quiescence_checkmated() {
+        assert_eq!(-MATE, quiescence("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1"));
+    }
+
+    #[rstest]
+    #[case::checkmated("7k/8/8/8/8/8/5PPP/r5K1 w - - 0 1", -MATE)]
+    #[case::stalemate("7k/8/8/8/8/8/2q5/K7 w - - 0 1", 0)]
+    fn test_no_legal_moves(#[case] fen: &str, #[case] expected: i32) {
+        let (best, iterations) = search(fen, depth(3));
+        assert_eq!(None, best.best_move());
+        assert_eq!(expected, best.score);
+        assert!(iterations.is_empty());
+    }
+
+    #[test]
+    fn test_iterations_deepen() {
+        let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+        let (best, iterations) = search(fen, depth(4));
+        let depths: Vec<u32> = iterations.iter().map(|it| it.depth).collect();
+        assert_eq!(vec![1, 2, 3, 4], depths);
+        assert_eq!(Some(&best), iterations.last());
+        assert!(iterations.windows(2).all(|w| w[0].nodes < w[1].nodes));
+        // The principal variation continues into the quiescence search that
+        // resolved its leaf, so past the nominal depth it may only hold the
+        // moves quiescence search plays.
+        for it in &iterations {
+            let mut pos = Position::from_str(fen).unwrap();
+            for (ply, &mv) in it.pv.iter().enumerate() {
+                assert!(pos.legal_moves().contains(&mv), "{:?}", it.pv);
+                let tactical = mv.captured().is_some() || mv.promoted().is_some() || pos.in_check();
+                assert!(ply < it.depth as usize || tactical, "{:?}", it.pv);
+                pos.make_move(mv);
+            }
+        }
+        assert!(best.score > -INFINITY && best.score < INFINITY);
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let limits = Limits {
+            nodes: Some(2_000),
+            ..Limits::default()
+        };
+        let (best, iterations) = search(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            limits,
+        );
+        assert!(best.best_move().is_some());
+        assert!(iterations.iter().all(|it| it.nodes <= 2_000));
+    }
+
+    #[test]
+    fn test_reuses_transposition_table() {
+        let tt = Arc::new(TranspositionTable::default());
+        let limits = Limits {
+            depth: Some(5),
+            ..Limits::default()
+        };
+        let mut nodes = Vec::new();
+        for _ in 0..2 {
+            let mut pos = Position::default();
+            let mut searcher = Searcher::new(
+                limits.clone(),
+                Arc::new(AtomicBool::new(false)),
+                Arc::clone(&tt),
+            );
+            searcher.search(&mut pos, |_| {});
+            nodes.push(searcher.nodes());
+        }
+        assert!(nodes[1] < nodes[0], "{nodes:?}");
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let limits = Limits {
+            time: Some(TimeManager::fixed(
+                Duration::from_millis(50),
+                Duration::ZERO,
+            )),
+            ..Limits::default()
+        };
+        let start = Instant::now();
+        let (best, _) = search(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            limits,
+        );
+        assert!(best.best_move().is_some());
+        assert!(start.elapsed() < Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_stop_flag() {
+        let mut pos = Position::default();
+        let mut searcher = Searcher::new(
+            Limits::default(),
+            Arc::new(AtomicBool::new(true)),
+            Arc::default(),
+        );
+        let best = searcher.search(&mut pos, |_| panic!("no iteration can complete"));
+        assert!(best.best_move().is_some());
+        assert_eq!(0, searcher.nodes());
+    }
+
+    #[test]
+    fn test_root_moves() {
+        let pos = Position::default();
+        let only = pos.legal_moves()[7];
+        let limits = Limits {
+            depth: Some(2),
+            root_moves: vec![only],
+            ..Limits::default()
+        };
+        let (best, _) = search(&pos.to_string(), limits);
+        assert_eq!(Some(only), best.best_move());
+    }
+
+    #[test]
+    fn test_mate_limit_stops_early() {
+        let limits = Limits {
+            mate: Some(1),
+            ..Limits::default()
+        };
+        let (best, iterations) = search("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", limits);
+        assert_eq!(Some(1), mate_in(best.score));
+        assert_eq!(1, iterations.len());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::attacks::ATTACKS;
+use crate::bitboard::Bitboard;
+use crate::board::{EIGHTH_RANK, FIRST_RANK};
+use crate::r#move::Move;
+use crate::piece::Piece;
+use crate::position::Position;
+
+/// The pieces in the order in which they join an exchange, i.e. least
+/// valuable first.
+const ATTACKERS: [Piece; 6] = [
+    Piece::WPawn,
+    Piece::WKnight,
+    Piece::WBishop,
+    Piece::WRook,
+    Piece::WQueen,
+    Piece::WKing,
+];
+
+impl Position {
+    /// Statically evaluates the exchange started by `mv` on its target
+    /// square, returning the material the moving side wins (or loses, if
+    /// negative) in centipawns when both sides keep recapturing with their
+    /// least valuable piece for as long as it pays off.
+    ///
+    /// Sliders lined up behind an attacker join the exchange once it has
+    /// moved. Pins are ignored, but the king never captures into a square
+    /// the opponent still attacks.
+    ///
+    /// See <https://www.chessprogramming.org/Static_Exchange_Evaluation>
+    pub fn see(&self, mv: Move) -> i32 {
+        if mv.is_castle() || mv.is_null() {
+            return 0;
+        }
+
+        let from = mv.from().expect("move has a valid source square");
+        let to = mv.to().expect("move has a valid target square");
+        let moved = mv.moved().expect("move has a moving piece");
+        let pieces = self.pieces();
+
+        let mut occupied = self.occupied() ^ from.bitboard();
+        if mv.is_en_passant() {
+            occupied ^= Position::capture_square(mv, to).bitboard();
+        }
+
+        // swap[i] is the material balance after the i-th capture, seen from
+        // the side that made it, assuming the exchange stops there.
+        let mut swap = Vec::with_capacity(32);
+        let value = |piece| self.piece_value(piece);
+        let promotion = mv
+            .promoted()
+            .map_or(0, |piece| value(piece) - value(Piece::WPawn));
+        swap.push(mv.captured().map_or(0, value) + promotion);
+        let mut on_square = mv.promoted().map_or(value(moved), value);
+
+        let queens = pieces[Piece::WQueen] | pieces[Piece::BQueen];
+        let diagonal = pieces[Piece::WBishop] | pieces[Piece::BBishop] | queens;
+        let straight = pieces[Piece::WRook] | pieces[Piece::BRook] | queens;
+        let promotes = (to.bitboard() & Bitboard::new(FIRST_RANK | EIGHTH_RANK)).bool();
+
+        let mut attackers = self.attackers_to(to, occupied) & occupied;
+        let mut side = !moved.color();
+        loop {
+            let ours = attackers & pieces[side];
+            let Some((attacker, sq)) = ATTACKERS.into_iter().find_map(|piece| {
+                let piece = piece.with_color(side);
+                (ours & pieces[piece]).lsb().map(|sq| (piece, sq))
+            }) else {
+                break;
+            };
+
+            let remaining = occupied ^ sq.bitboard();
+            let revealed = (ATTACKS.bishop(to, remaining) & diagonal)
+                | (ATTACKS.rook(to, remaining) & straight);
+            let next_attackers = (attackers | revealed) & remaining;
+            if attacker == Piece::WKing.with_color(side) && (next_attackers & pieces[!side]).bool()
+            {
+                break;
+            }
+
+            let is_pawn = attacker == Piece::WPawn.with_color(side);
+            let promotion = if is_pawn && promotes {
+                value(Piece::WQueen) - value(attacker)
+            } else {
+                0
+            };
+            let last = *swap
+                .last()
+                .expect("swap list starts with the first capture");
+            swap.push(on_square + promotion - last);
+            on_square = value(attacker) + promotion;
+
+            occupied = remaining;
+            attackers = next_attackers;
+            side = !side;
+        }
+
+        // Either side may stop recapturing once it would lose material.
+        while swap.len() > 1 {
+            let last = swap.pop().expect("swap list has at least two entries");
+            let prev = swap.last_mut().expect("swap list has at least one entry");
+            *prev = -(-*prev).max(last);
+        }
+        swap[0]
+    }
+
+    /// Returns `true` if the exchange started by `mv` wins at least
+    /// `threshold` centipawns according to [`Self::see`].
+    pub fn see_ge(&self, mv: Move, threshold: i32) -> bool {
+        self.see(mv) >= threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rstest::rstest;
+
+    use crate::r#move::Move;
+    use crate::piece::Piece;
+    use crate::position::Position;
+    use crate::square::Square;
+
+    fn find(pos: &Position, from: Square, to: Square, promoted: Option<Piece>) -> Move {
+        pos.legal_moves()
+            .into_iter()
+            .find(|mv| mv.from() == Some(from) && mv.to() == Some(to) && mv.promoted() == promoted)
+            .expect("move is legal")
+    }
+
+    /// The expected exchange is given as the pieces the moving side wins
+    /// and those it loses.
+    #[rstest]
+    #[case::undefended_pawn(
+        "1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1",
+        Square::E1,
+        Square::E5,
+        None,
+        &[Piece::WPawn],
+        &[]
+    )]
+    #[case::xray_exchange(
+        "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+        Square::D3,
+        Square::E5,
+        None,
+        &[Piece::WPawn],
+        &[Piece::WKnight]
+    )]
+    #[case::equal_trade(
+        "4k3/8/3r4/8/8/8/8/3RK3 w - - 0 1",
+        Square::D1,
+        Square::D6,
+        None,
+        &[Piece::WRook],
+        &[]
+    )]
+    #[case::defended_rook(
+        "4k3/2p5/3r4/8/8/8/8/3RK3 w - - 0 1",
+        Square::D1,
+        Square::D6,
+        None,
+        &[Piece::WRook],
+        &[Piece::WRook]
+    )]
+    #[case::quiet_move_into_attack(
+        "4k3/8/4p3/8/8/8/8/3QK3 w - - 0 1",
+        Square::D1,
+        Square::D5,
+        None,
+        &[],
+        &[Piece::WQueen]
+    )]
+    #[case::safe_quiet_move(
+        "4k3/8/8/8/8/8/8/3QK3 w - - 0 1",
+        Square::D1,
+        Square::D5,
+        None,
+        &[],
+        &[]
+    )]
+    #[case::king_cannot_recapture(
+        "8/8/4k3/3p4/8/8/3R4/3QK3 w - - 0 1",
+        Square::D2,
+        Square::D5,
+        None,
+        &[Piece::WPawn],
+        &[]
+    )]
+    #[case::king_recaptures(
+        "8/8/4k3/3p4/8/8/3R4/4K3 w - - 0 1",
+        Square::D2,
+        Square::D5,
+        None,
+        &[Piece::WPawn],
+        &[Piece::WRook]
+    )]
+    #[case::en_passant(
+        "3rk3/8/8/3pP3/8/8/8/3RK3 w - d6 0 1",
+        Square::E5,
+        Square::D6,
+        None,
+        &[Piece::WPawn],
+        &[]
+    )]
+    #[case::promotion(
+        "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1",
+        Square::B7,
+        Square::B8,
+        Some(Piece::WQueen),
+        &[Piece::WQueen],
+        &[Piece::WPawn]
+    )]
+    #[case::defended_promotion(
+        "r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1",
+        Square::B7,
+        Square::B8,
+        Some(Piece::WQueen),
+        &[],
+        &[Piece::WPawn]
+    )]
+    #[case::promotion_capture(
+        "rn2k3/1P6/8/8/8/8/8/4K3 w - - 0 1",
+        Square::B7,
+        Square::A8,
+        Some(Piece::WQueen),
+        &[Piece::WRook, Piece::WQueen],
+        &[Piece::WPawn]
+    )]
+    fn test_see(
+        #[case] fen: &str,
+        #[case] from: Square,
+        #[case] to: Square,
+        #[case] promoted: Option<Piece>,
+        #[case] won: &[Piece],
+        #[case] lost: &[Piece],
+    ) {
+        let pos = Position::from_str(fen).unwrap();
+        let value = |pieces: &[Piece]| {
+            pieces
+                .iter()
+                .map(|&piece| pos.piece_value(piece))
+                .sum::<i32>()
+        };
+        let expected = value(won) - value(lost);
+        let mv = find(&pos, from, to, promoted);
+        assert_eq!(expected, pos.see(mv));
+        assert!(pos.see_ge(mv, expected));
+        assert!(!pos.see_ge(mv, expected + 1));
+    }
+}
@@ -0,0 +1,229 @@
+//! Time management.
+//!
+//! Under a clock, each search gets a soft deadline, after which no new
+//! iteration is started, and a hard deadline, at which the search is
+//! aborted. The soft deadline is stretched while the search is unsure of
+//! its best move, i.e. when the move keeps changing or the score drops.
+//! See <https://www.chessprogramming.org/Time_Management>
+
+use std::time::Duration;
+
+use crate::r#move::Move;
+use crate::search::{Iteration, MATE_BOUND};
+
+/// The time reserved on every move for communication with the GUI.
+pub const DEFAULT_MOVE_OVERHEAD: Duration = Duration::from_millis(10);
+
+/// The largest move overhead that can be requested.
+pub const MAX_MOVE_OVERHEAD: Duration = Duration::from_millis(5000);
+
+/// The number of moves assumed to be left in a game without a move count.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+/// How many times the soft deadline the hard deadline may be.
+const HARD_LIMIT_FACTOR: u32 = 3;
+
+/// The score drop in centipawns past which the soft deadline is no longer
+/// stretched any further.
+const MAX_SCORE_DROP: i32 = 50;
+
+/// Deadlines for a single search and the state needed to adjust them as
+/// iterations complete.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeManager {
+    soft: Duration,
+    hard: Duration,
+
+    /// Whether the deadlines were fixed by the GUI and must not be adjusted.
+    fixed: bool,
+
+    /// The best move and score of the previous iteration.
+    best_move: Option<Move>,
+    score: Option<i32>,
+
+    /// How often the best move changed recently, in percent. It grows by
+    /// 100 whenever the best move changes and halves on every iteration.
+    instability: u32,
+
+    /// The factor applied to the soft deadline, in percent.
+    scale: u32,
+}
+
+impl TimeManager {
+    /// Creates deadlines for searching exactly `movetime`, less `overhead`.
+    pub fn fixed(movetime: Duration, overhead: Duration) -> Self {
+        let limit = movetime.saturating_sub(overhead);
+        Self::new(limit, limit, true)
+    }
+
+    /// Creates deadlines for a player with `time` left on the clock, who
+    /// gains `increment` per move and has `movestogo` moves to play until
+    /// the next time control, if any. `overhead` is kept in reserve.
+    pub fn clock(
+        time: Duration,
+        increment: Duration,
+        movestogo: Option<u32>,
+        overhead: Duration,
+    ) -> Self {
+        let available = time.saturating_sub(overhead);
+        let moves = movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let base = available / moves + increment * 3 / 4;
+
+        let hard = (base * HARD_LIMIT_FACTOR).min(available * 4 / 5);
+        Self::new(base.min(hard), hard, false)
+    }
+
+    fn new(soft: Duration, hard: Duration, fixed: bool) -> Self {
+        Self {
+            soft,
+            hard,
+            fixed,
+            best_move: None,
+            score: None,
+            instability: 0,
+            scale: 100,
+        }
+    }
+
+    /// Returns the time after which no new iteration should be started.
+    pub fn soft_limit(&self) -> Duration {
+        (self.soft * self.scale / 100).min(self.hard)
+    }
+
+    /// Returns the time at which the search must stop.
+    pub fn hard_limit(&self) -> Duration {
+        self.hard
+    }
+
+    /// Adjusts the soft deadline after `iteration` completes, stretching it
+    /// if the best move changed or the score dropped since the previous one.
+    pub fn update(&mut self, iteration: &Iteration) {
+        if self.fixed {
+            return;
+        }
+
+        let best_move = iteration.best_move();
+        let changed = self.best_move.is_some() && self.best_move != best_move;
+        self.instability = self.instability / 2 + if changed { 100 } else { 0 };
+
+        // Mate scores jump by far more than any positional concern.
+        let drop = match self.score {
+            Some(previous) if previous.abs() < MATE_BOUND && iteration.score.abs() < MATE_BOUND => {
+                (previous - iteration.score).clamp(0, MAX_SCORE_DROP)
+            }
+            _ => 0,
+        };
+
+        self.scale = (100 + self.instability / 2) * (100 + drop as u32) / 100;
+        self.best_move = best_move;
+        self.score = Some(iteration.score);
+    }
+
+    /// Returns `true` if another iteration should not be started once
+    /// `elapsed` has passed since the search started.
+    pub fn soft_expired(&self, elapsed: Duration) -> bool {
+        elapsed >= self.soft_limit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use rstest::rstest;
+
+    use crate::position::Position;
+    use crate::search::{Iteration, MATE};
+
+    use super::TimeManager;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[rstest]
+    #[case::movetime(TimeManager::fixed(ms(1510), ms(10)), 1500, 1500)]
+    #[case::sudden_death(TimeManager::clock(ms(60010), ms(0), None, ms(10)), 2000, 6000)]
+    #[case::increment(TimeManager::clock(ms(30010), ms(1000), None, ms(10)), 1750, 5250)]
+    #[case::moves_to_go(TimeManager::clock(ms(60010), ms(0), Some(10), ms(10)), 6000, 18000)]
+    #[case::last_move(TimeManager::clock(ms(10010), ms(0), Some(1), ms(10)), 8000, 8000)]
+    #[case::low_on_time(TimeManager::clock(ms(40), ms(1000), None, ms(50)), 0, 0)]
+    fn test_deadlines(#[case] tm: TimeManager, #[case] soft: u64, #[case] hard: u64) {
+        assert_eq!(ms(soft), tm.soft_limit());
+        assert_eq!(ms(hard), tm.hard_limit());
+    }
+
+    fn iteration(mv: usize, score: i32) -> Iteration {
+        let pos = Position::from_str("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+        Iteration {
+            depth: 1,
+            score,
+            nodes: 0,
+            time: Duration::ZERO,
+            pv: vec![pos.legal_moves()[mv]],
+        }
+    }
+
+    #[test]
+    fn test_stable_search_keeps_soft_limit() {
+        let mut tm = TimeManager::clock(ms(60010), ms(0), None, ms(10));
+        for score in [20, 25, 30] {
+            tm.update(&iteration(0, score));
+        }
+        assert_eq!(ms(2000), tm.soft_limit());
+        assert!(!tm.soft_expired(ms(1999)));
+        assert!(tm.soft_expired(ms(2000)));
+    }
+
+    #[test]
+    fn test_unstable_best_move_extends_soft_limit() {
+        let mut tm = TimeManager::clock(ms(60010), ms(0), None, ms(10));
+        tm.update(&iteration(0, 20));
+        tm.update(&iteration(1, 20));
+        assert_eq!(ms(3000), tm.soft_limit());
+
+        // The extension wears off once the best move settles.
+        tm.update(&iteration(1, 20));
+        assert_eq!(ms(2500), tm.soft_limit());
+        for _ in 0..8 {
+            tm.update(&iteration(1, 20));
+        }
+        assert_eq!(ms(2000), tm.soft_limit());
+    }
+
+    #[rstest]
+    #[case::small_drop(20, 10, 2200)]
+    #[case::large_drop(20, -200, 3000)]
+    #[case::score_rises(20, 80, 2000)]
+    #[case::mate_found(20, MATE - 5, 2000)]
+    fn test_score_drop_extends_soft_limit(
+        #[case] before: i32,
+        #[case] after: i32,
+        #[case] soft: u64,
+    ) {
+        let mut tm = TimeManager::clock(ms(60010), ms(0), None, ms(10));
+        tm.update(&iteration(0, before));
+        tm.update(&iteration(0, after));
+        assert_eq!(ms(soft), tm.soft_limit());
+    }
+
+    #[test]
+    fn test_soft_limit_never_exceeds_hard_limit() {
+        let mut tm = TimeManager::clock(ms(10010), ms(0), Some(2), ms(10));
+        assert_eq!(ms(5000), tm.soft_limit());
+        for i in 0..10 {
+            tm.update(&iteration(i % 2, 100 - 100 * i as i32));
+        }
+        assert_eq!(tm.hard_limit(), tm.soft_limit());
+    }
+
+    #[test]
+    fn test_fixed_time_is_not_adjusted() {
+        let mut tm = TimeManager::fixed(ms(1000), ms(0));
+        tm.update(&iteration(0, 100));
+        tm.update(&iteration(1, -100));
+        assert_eq!(ms(1000), tm.soft_limit());
+        assert_eq!(ms(1000), tm.hard_limit());
+    }
+}
@@ -0,0 +1,469 @@
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+use crate::r#move::Move;
+use crate::search::MATE_BOUND;
+
+/// The table size used until a different one is requested, in megabytes.
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// The largest table size that can be requested, in megabytes.
+pub const MAX_HASH_MB: usize = 65536;
+
+/// The number of entries sharing a bucket, chosen so a bucket fills a
+/// typical 64-byte cache line.
+const BUCKET_SIZE: usize = 4;
+
+/// Generations are stored in six bits and wrap around.
+const GENERATION_MASK: u8 = 0b11_1111;
+
+/// How a stored score relates to the true score of the position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact, i.e. it fell inside the search window.
+    Exact,
+    /// The search failed high, so the true score is at least this score.
+    Lower,
+    /// The search failed low, so the true score is at most this score.
+    Upper,
+}
+
+/// The search results stored for a single position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TtEntry {
+    /// The depth the position was searched to, in plies.
+    pub depth: u32,
+
+    /// How [`Self::score`] bounds the true score.
+    pub bound: Bound,
+
+    /// The score from the point of view of the side to move, with mate
+    /// scores relative to the ply the table was probed at.
+    pub score: i32,
+
+    /// The lower 16 bits of the best move, i.e. its squares and flags.
+    best_move: u16,
+}
+
+impl TtEntry {
+    /// Returns `true` if `mv` is the best move stored for the position.
+    pub fn is_best_move(&self, mv: Move) -> bool {
+        self.best_move != 0 && Self::compact(mv) == self.best_move
+    }
+
+    /// Packs the source and target squares and the flags of `mv`, which
+    /// identify it uniquely among the moves of a position, into 16 bits.
+    fn compact(mv: Move) -> u16 {
+        mv.raw_value() as u16
+    }
+}
+
+/// A single slot of the table. The key is stored XORed with the data, so
+/// an entry torn by two threads writing it at once fails verification
+/// instead of returning another position's data.
+///
+/// See <https://www.chessprogramming.org/Shared_Hash_Table#Lockless>
+#[derive(Default)]
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// The layout of [`Slot::data`], from the least significant bit:
+/// the compact best move (16 bits), the score (16 bits), the depth (8 bits),
+/// the bound (2 bits, zero for an empty slot) and the generation (6 bits).
+impl Slot {
+    fn pack(entry: &TtEntry, generation: u8) -> u64 {
+        let bound = match entry.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+
+        u64::from(entry.best_move)
+            | (u64::from(entry.score as i16 as u16) << 16)
+            | (u64::from(entry.depth.min(u8::MAX as u32)) << 32)
+            | (bound << 40)
+            | (u64::from(generation & GENERATION_MASK) << 42)
+    }
+
+    fn unpack(data: u64) -> Option<TtEntry> {
+        let bound = match (data >> 40) & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => return None,
+        };
+
+        Some(TtEntry {
+            depth: ((data >> 32) & 0xFF) as u32,
+            bound,
+            score: i32::from((data >> 16) as u16 as i16),
+            best_move: data as u16,
+        })
+    }
+
+    fn generation(data: u64) -> u8 {
+        (data >> 42) as u8 & GENERATION_MASK
+    }
+
+    /// Returns the data stored for `hash`, or [`None`] if the slot holds
+    /// another position (or a torn write).
+    fn load(&self, hash: u64) -> Option<u64> {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key.load(Ordering::Relaxed);
+        (key ^ data == hash && data != 0).then_some(data)
+    }
+
+    fn store(&self, hash: u64, data: u64) {
+        self.key.store(hash ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+#[repr(align(64))]
+#[derive(Default)]
+struct Bucket([Slot; BUCKET_SIZE]);
+
+/// A hash table of search results keyed by [`crate::position::Position::hash`],
+/// which can be shared between search threads without locking.
+///
+/// Each key maps to a bucket of a few entries. When a bucket is full the
+/// entry replaced is the one that is least valuable to keep: the shallowest,
+/// preferring entries left over from earlier searches.
+///
+/// See <https://www.chessprogramming.org/Transposition_Table>
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    generation: AtomicU8,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl TranspositionTable {
+    /// Creates a table taking up at most `mb` megabytes, rounded down to a
+    /// power of two number of buckets.
+    pub fn new(mb: usize) -> Self {
+        let mut tt = Self {
+            buckets: Vec::new(),
+            generation: AtomicU8::new(0),
+        };
+        tt.resize(mb);
+        tt
+    }
+
+    /// Resizes the table to take up at most `mb` megabytes, discarding
+    /// every entry.
+    pub fn resize(&mut self, mb: usize) {
+        let buckets = (mb * 1024 * 1024 / size_of::<Bucket>()).max(1);
+        let buckets = 1 << buckets.ilog2();
+        self.buckets = Vec::new();
+        self.buckets.resize_with(buckets, Bucket::default);
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    /// Removes every entry from the table.
+    pub fn clear(&self) {
+        for slot in self.buckets.iter().flat_map(|bucket| &bucket.0) {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the size of the table in bytes.
+    pub fn size(&self) -> usize {
+        self.buckets.len() * size_of::<Bucket>()
+    }
+
+    /// Marks the start of a new search, so that entries stored by earlier
+    /// searches are replaced first.
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Looks up the entry for the position with the given `hash`, probed
+    /// `ply` plies from the root.
+    pub fn probe(&self, hash: u64, ply: usize) -> Option<TtEntry> {
+        let data = self
+            .bucket(hash)
+            .0
+            .iter()
+            .find_map(|slot| slot.load(hash))?;
+        let mut entry = Slot::unpack(data)?;
+        entry.score = Self::score_from_tt(entry.score, ply);
+        Some(entry)
+    }
+
+    /// Stores the result of searching the position with the given `hash`
+    /// `ply` plies from the root.
+    pub fn store(
+        &self,
+        hash: u64,
+        depth: u32,
+        bound: Bound,
+        score: i32,
+        ply: usize,
+        best_move: Option<Move>,
+    ) {
+        let generation = self.generation();
+        let bucket = &self.bucket(hash).0;
+
+        // Keep the best move of an earlier search of this position if this
+        // one did not find any, e.g. because every move failed low.
+        let existing = bucket.iter().find_map(|slot| slot.load(hash));
+        let best_move = match (best_move, existing) {
+            (Some(mv), _) => TtEntry::compact(mv),
+            (None, Some(data)) => data as u16,
+            (None, None) => 0,
+        };
+
+        let entry = TtEntry {
+            depth,
+            bound,
+            score: Self::score_to_tt(score, ply),
+            best_move,
+        };
+        let data = Slot::pack(&entry, generation);
+
+        let slot = bucket
+            .iter()
+            .find(|slot| slot.load(hash).is_some())
+            .unwrap_or_else(|| {
+                bucket
+                    .iter()
+                    .min_by_key(|slot| self.worth(slot.data.load(Ordering::Relaxed)))
+                    .expect("buckets are not empty")
+            });
+        slot.store(hash, data);
+    }
+
+    /// Estimates how full the table is in permille, counting only entries
+    /// stored during the current search, as reported by UCI `hashfull`.
+    pub fn hashfull(&self) -> usize {
+        let generation = self.generation();
+        let sampled: Vec<u64> = self
+            .buckets
+            .iter()
+            .take(1000 / BUCKET_SIZE)
+            .flat_map(|bucket| &bucket.0)
+            .map(|slot| slot.data.load(Ordering::Relaxed))
+            .collect();
+        let used = sampled
+            .iter()
+            .filter(|&&data| data != 0 && Slot::generation(data) == generation)
+            .count();
+
+        used * 1000 / sampled.len()
+    }
+
+    fn generation(&self) -> u8 {
+        self.generation.load(Ordering::Relaxed) & GENERATION_MASK
+    }
+
+    fn bucket(&self, hash: u64) -> &Bucket {
+        &self.buckets[hash as usize & (self.buckets.len() - 1)]
+    }
+
+    /// Returns how valuable it is to keep the entry holding `data`: empty
+    /// slots are worth nothing, and every search an entry ages costs it as
+    /// much as eight plies of depth.
+    fn worth(&self, data: u64) -> i32 {
+        match Slot::unpack(data) {
+            None => i32::MIN,
+            Some(entry) => {
+                let age = self.generation().wrapping_sub(Slot::generation(data)) & GENERATION_MASK;
+                entry.depth as i32 - 8 * age as i32
+            }
+        }
+    }
+
+    /// Mate scores are relative to the root, but a position can be reached
+    /// at any ply, so they are stored relative to the position itself.
+    fn score_to_tt(score: i32, ply: usize) -> i32 {
+        if score > MATE_BOUND {
+            score + ply as i32
+        } else if score < -MATE_BOUND {
+            score - ply as i32
+        } else {
+            score
+        }
+    }
+
+    /// Converts a mate score stored by [`Self::score_to_tt`] back to being
+    /// relative to the root of a search that is `ply` plies above it.
+    fn score_from_tt(score: i32, ply: usize) -> i32 {
+        if score > MATE_BOUND {
+            score - ply as i32
+        } else if score < -MATE_BOUND {
+            score + ply as i32
+        } else {
+            score
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use rstest::rstest;
+
+    use crate::r#move::Move;
+    use crate::piece::Piece;
+    use crate::search::MATE;
+    use crate::square::Square;
+
+    use super::{Bound, Bucket, TranspositionTable};
+
+    fn e2e4() -> Move {
+        Move::encode(
+            Square::E2,
+            Square::E4,
+            Move::DOUBLE_PAWN_PUSH,
+            Piece::WPawn,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_size_is_power_of_two() {
+        let tt = TranspositionTable::new(16);
+        assert_eq!(16 * 1024 * 1024, tt.size());
+        assert_eq!(64, size_of::<Bucket>());
+
+        let mut tt = TranspositionTable::new(3);
+        assert_eq!(2 * 1024 * 1024, tt.size());
+        tt.resize(1);
+        assert_eq!(1024 * 1024, tt.size());
+    }
+
+    #[test]
+    fn test_store_and_probe() {
+        let tt = TranspositionTable::new(1);
+        assert_eq!(None, tt.probe(0xDEADBEEF, 0));
+
+        tt.store(0xDEADBEEF, 7, Bound::Lower, -42, 3, Some(e2e4()));
+        let entry = tt.probe(0xDEADBEEF, 3).unwrap();
+        assert_eq!(7, entry.depth);
+        assert_eq!(Bound::Lower, entry.bound);
+        assert_eq!(-42, entry.score);
+        assert!(entry.is_best_move(e2e4()));
+
+        // A different key mapping to the same bucket must not match.
+        let other = 0xDEADBEEF ^ (1 << 60);
+        assert_eq!(None, tt.probe(other, 3));
+    }
+
+    #[test]
+    fn test_store_keeps_best_move() {
+        let tt = TranspositionTable::new(1);
+        tt.store(1, 3, Bound::Exact, 10, 0, Some(e2e4()));
+        tt.store(1, 4, Bound::Upper, -5, 0, None);
+
+        let entry = tt.probe(1, 0).unwrap();
+        assert_eq!(4, entry.depth);
+        assert!(entry.is_best_move(e2e4()));
+    }
+
+    #[rstest]
+    #[case::mate_in_three(MATE - 5, 4, MATE - 1, 2, MATE - 3)]
+    #[case::mated(-MATE + 6, 4, -MATE + 2, 1, -MATE + 3)]
+    #[case::not_mate(150, 9, 150, 2, 150)]
+    fn test_mate_scores_are_relative_to_position(
+        #[case] score: i32,
+        #[case] ply: usize,
+        #[case] stored: i32,
+        #[case] probe_ply: usize,
+        #[case] probed: i32,
+    ) {
+        assert_eq!(stored, TranspositionTable::score_to_tt(score, ply));
+
+        let tt = TranspositionTable::new(1);
+        tt.store(99, 1, Bound::Exact, score, ply, None);
+        assert_eq!(probed, tt.probe(99, probe_ply).unwrap().score);
+    }
+
+    #[test]
+    fn test_replacement_prefers_shallow_and_old_entries() {
+        let tt = TranspositionTable::new(1);
+        let buckets = tt.buckets.len() as u64;
+        // Keys that all map to bucket 0.
+        let keys: Vec<u64> = (1..=5).map(|i| i * buckets).collect();
+
+        for (i, &key) in keys[..4].iter().enumerate() {
+            tt.store(key, 10 + i as u32, Bound::Exact, 0, 0, None);
+        }
+        tt.store(keys[4], 1, Bound::Exact, 0, 0, None);
+        assert_eq!(
+            None,
+            tt.probe(keys[0], 0),
+            "the shallowest entry is replaced"
+        );
+        assert!(keys[1..].iter().all(|&key| tt.probe(key, 0).is_some()));
+
+        // Entries left over from earlier searches make way even when deep.
+        tt.new_search();
+        tt.new_search();
+        for &key in &[keys[1], keys[3], keys[4]] {
+            let depth = tt.probe(key, 0).unwrap().depth;
+            tt.store(key, depth, Bound::Exact, 0, 0, None);
+        }
+        tt.store(keys[0], 1, Bound::Exact, 0, 0, None);
+        assert!(tt.probe(keys[0], 0).is_some());
+        assert_eq!(None, tt.probe(keys[2], 0));
+        assert!(tt.probe(keys[4], 0).is_some());
+    }
+
+    #[test]
+    fn test_clear_and_hashfull() {
+        let tt = TranspositionTable::new(1);
+        assert_eq!(0, tt.hashfull());
+
+        for key in 0..tt.buckets.len() as u64 * 2 {
+            tt.store(key, 1, Bound::Exact, 0, 0, None);
+        }
+        assert_eq!(500, tt.hashfull());
+
+        tt.new_search();
+        assert_eq!(0, tt.hashfull(), "only the current search counts");
+
+        tt.clear();
+        assert_eq!(None, tt.probe(1, 0));
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let tt = Arc::new(TranspositionTable::new(1));
+        let threads: Vec<_> = (0..4u64)
+            .map(|t| {
+                let tt = Arc::clone(&tt);
+                thread::spawn(move || {
+                    for key in (t * 1000)..(t * 1000 + 1000) {
+                        tt.store(
+                            key,
+                            (key % 50) as u32,
+                            Bound::Exact,
+                            key as i32 % 1000,
+                            0,
+                            None,
+                        );
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        for key in 0..4000 {
+            if let Some(entry) = tt.probe(key, 0) {
+                assert_eq!((key % 50) as u32, entry.depth);
+                assert_eq!(key as i32 % 1000, entry.score);
+            }
+        }
+    }
+}
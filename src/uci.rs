@@ -0,0 +1,865 @@
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::color::Color;
+use crate::error::{DiogenesError, DiogenesResult};
+use crate::r#move::Move;
+use crate::position::{Position, STARTING_FEN};
+use crate::search::{Iteration, Limits, Searcher, mate_in};
+use crate::timeman::{DEFAULT_MOVE_OVERHEAD, MAX_MOVE_OVERHEAD, TimeManager};
+use crate::tt::{DEFAULT_HASH_MB, MAX_HASH_MB, TranspositionTable};
+
+const ENGINE_NAME: &str = concat!("Diogenes ", env!("CARGO_PKG_VERSION"));
+const ENGINE_AUTHOR: &str = "Amogh Dambal";
+
+/// Destination for everything the engine sends to the GUI, shared between
+/// the command loop and the search thread.
+type Output = Arc<Mutex<dyn Write + Send>>;
+
+/// Writes a single line to the GUI and flushes it immediately, since GUIs
+/// react to each line as it arrives.
+fn send(output: &Output, line: &str) {
+    let mut output = output.lock().expect("output lock is never poisoned");
+    // There is no one left to report a failure to once the GUI has gone away.
+    let _ = writeln!(output, "{line}").and_then(|_| output.flush());
+}
+
+/// The limits sent with a UCI `go` command. Times are in milliseconds.
+///
+/// See <https://www.wbec-ridderkerk.nl/html/UCIProtocol.html>
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GoOptions {
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u32>,
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub mate: Option<u32>,
+    pub movetime: Option<u64>,
+    pub infinite: bool,
+    pub ponder: bool,
+    pub perft: Option<u32>,
+    pub searchmoves: Vec<String>,
+}
+
+impl FromStr for GoOptions {
+    type Err = DiogenesError;
+
+    /// Parses the arguments following `go`, e.g. `wtime 300000 btime 300000 movestogo 40`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn value<T: FromStr>(name: &str, token: Option<&str>) -> DiogenesResult<T> {
+            token.and_then(|t| t.parse().ok()).ok_or_else(|| {
+                DiogenesError::InvalidUciError(format!("go {name} expects a number"))
+            })
+        }
+        // Some GUIs send slightly negative clock times once a player has flagged.
+        fn time(name: &str, token: Option<&str>) -> DiogenesResult<u64> {
+            value::<i64>(name, token).map(|t| t.max(0) as u64)
+        }
+
+        let mut options = GoOptions::default();
+        let mut tokens = s.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "wtime" => options.wtime = Some(time(token, tokens.next())?),
+                "btime" => options.btime = Some(time(token, tokens.next())?),
+                "winc" => options.winc = Some(time(token, tokens.next())?),
+                "binc" => options.binc = Some(time(token, tokens.next())?),
+                "movetime" => options.movetime = Some(time(token, tokens.next())?),
+                "movestogo" => options.movestogo = Some(value(token, tokens.next())?),
+                "depth" => options.depth = Some(value(token, tokens.next())?),
+                "nodes" => options.nodes = Some(value(token, tokens.next())?),
+                "mate" => options.mate = Some(value(token, tokens.next())?),
+                "perft" => options.perft = Some(value(token, tokens.next())?),
+                "infinite" => options.infinite = true,
+                "ponder" => options.ponder = true,
+                // The move list runs until the next keyword, or the end of the line.
+                "searchmoves" => {
+                    let mut rest = tokens.clone().peekable();
+                    while let Some(mv) = rest.next_if(|t| Self::is_move_text(t)) {
+                        options.searchmoves.push(mv.to_string());
+                        tokens.next();
+                    }
+                }
+                _ => {
+                    return Err(DiogenesError::InvalidUciError(format!(
+                        "unknown go parameter {token:?}"
+                    )));
+                }
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+impl GoOptions {
+    /// Returns `true` if `token` looks like a move in long algebraic notation.
+    fn is_move_text(token: &str) -> bool {
+        let bytes = token.as_bytes();
+        (bytes.len() == 4 || bytes.len() == 5)
+            && (b'a'..=b'h').contains(&bytes[0])
+            && (b'1'..=b'8').contains(&bytes[1])
+            && (b'a'..=b'h').contains(&bytes[2])
+            && (b'1'..=b'8').contains(&bytes[3])
+    }
+}
+
+/// Holds back the `bestmove` of a search that finished before the protocol
+/// allows it to be sent: an `infinite` search before `stop`, or a ponder
+/// search before `stop` or `ponderhit`.
+#[derive(Default)]
+struct Release {
+    /// Set while a `go ponder` search is running and the GUI has not yet
+    /// sent `ponderhit`.
+    pondering: Mutex<bool>,
+    changed: Condvar,
+}
+
+impl Release {
+    fn set_pondering(&self, pondering: bool) {
+        *self
+            .pondering
+            .lock()
+            .expect("release lock is never poisoned") = pondering;
+        self.changed.notify_all();
+    }
+
+    /// Wakes a waiting worker to look at the stop flag again, which must
+    /// already be set.
+    fn notify(&self) {
+        let _pondering = self
+            .pondering
+            .lock()
+            .expect("release lock is never poisoned");
+        self.changed.notify_all();
+    }
+
+    /// Blocks until a finished search may send its `bestmove`, returning
+    /// `false` if it was stopped while still pondering and must send nothing.
+    fn wait(&self, infinite: bool, stop: &AtomicBool) -> bool {
+        let pondering = self
+            .pondering
+            .lock()
+            .expect("release lock is never poisoned");
+        let pondering = self
+            .changed
+            .wait_while(pondering, |pondering| {
+                (infinite || *pondering) && !stop.load(Ordering::Relaxed)
+            })
+            .expect("release lock is never poisoned");
+        !*pondering
+    }
+}
+
+/// A UCI engine session: it reads commands from the GUI, keeps track of the
+/// current position and runs searches on a background thread so that `stop`
+/// and `quit` are handled while the engine is thinking.
+pub struct Uci {
+    position: Position,
+    output: Output,
+    stop: Arc<AtomicBool>,
+    release: Arc<Release>,
+    /// The options of the running `go ponder` search, which is restarted
+    /// with them on `ponderhit`.
+    ponder: Option<GoOptions>,
+    worker: Option<JoinHandle<()>>,
+    tt: Arc<TranspositionTable>,
+    move_overhead: Duration,
+}
+
+impl Uci {
+    pub fn new(output: impl Write + Send + 'static) -> Self {
+        Self {
+            position: Position::default(),
+            output: Arc::new(Mutex::new(output)),
+            stop: Arc::new(AtomicBool::new(false)),
+            release: Arc::default(),
+            ponder: None,
+            worker: None,
+            tt: Arc::default(),
+            move_overhead: DEFAULT_MOVE_OVERHEAD,
+        }
+    }
+
+    /// Handles commands from `input` until `quit` is received or the input
+    /// is exhausted.
+    pub fn run(&mut self, input: impl BufRead) -> std::io::Result<()> {
+        for line in input.lines() {
+            if !self.handle(&line?) {
+                break;
+            }
+        }
+        self.stop_search();
+
+        Ok(())
+    }
+
+    /// Handles a single command line, returning `false` once the engine
+    /// should exit. Malformed commands are reported with an `info string`
+    /// and otherwise ignored, as the protocol requires.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let result = match command {
+            "uci" => {
+                send(&self.output, &format!("id name {ENGINE_NAME}"));
+                send(&self.output, &format!("id author {ENGINE_AUTHOR}"));
+                send(
+                    &self.output,
+                    &format!(
+                        "option name Hash type spin default {DEFAULT_HASH_MB} min 1 max {MAX_HASH_MB}"
+                    ),
+                );
+                send(
+                    &self.output,
+                    &format!(
+                        "option name Move Overhead type spin default {} min 0 max {}",
+                        DEFAULT_MOVE_OVERHEAD.as_millis(),
+                        MAX_MOVE_OVERHEAD.as_millis()
+                    ),
+                );
+                send(&self.output, "uciok");
+                Ok(())
+            }
+            "isready" => {
+                send(&self.output, "readyok");
+                Ok(())
+            }
+            "ucinewgame" => {
+                self.stop_search();
+                self.position = Position::default();
+                self.tt.clear();
+                Ok(())
+            }
+            "setoption" => self.set_option(args),
+            "position" => self.set_position(args),
+            "go" => self.go(args),
+            "stop" => {
+                // A search stopped while pondering still owes a `bestmove`.
+                self.release.set_pondering(false);
+                self.stop_search();
+                Ok(())
+            }
+            "ponderhit" => self.ponderhit(),
+            "quit" => return false,
+            "" => Ok(()),
+            _ => Err(DiogenesError::InvalidUciError(format!(
+                "unknown command {command:?}"
+            ))),
+        };
+
+        if let Err(err) = result {
+            send(&self.output, &format!("info string {err}"));
+        }
+        true
+    }
+
+    /// Returns the position that the next search will start from.
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Handles `setoption name <id> [value <x>]`. Option names are matched
+    /// case-insensitively, as the protocol requires.
+    fn set_option(&mut self, args: &str) -> DiogenesResult<()> {
+        let args = args.strip_prefix("name ").ok_or_else(|| {
+            DiogenesError::InvalidUciError(format!("expected name but found {args:?}"))
+        })?;
+        let (name, value) = match args.split_once(" value ") {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (args.trim(), None),
+        };
+
+        if name.eq_ignore_ascii_case("Hash") {
+            let mb = value
+                .and_then(|value| value.parse::<usize>().ok())
+                .filter(|mb| (1..=MAX_HASH_MB).contains(mb))
+                .ok_or_else(|| {
+                    DiogenesError::InvalidUciError(format!(
+                        "Hash expects a size between 1 and {MAX_HASH_MB} MB"
+                    ))
+                })?;
+            self.stop_search();
+            // The worker has been joined, so nothing else holds the table.
+            Arc::get_mut(&mut self.tt)
+                .expect("no search is running")
+                .resize(mb);
+            Ok(())
+        } else if name.eq_ignore_ascii_case("Move Overhead") {
+            self.move_overhead = value
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_millis)
+                .filter(|overhead| *overhead <= MAX_MOVE_OVERHEAD)
+                .ok_or_else(|| {
+                    DiogenesError::InvalidUciError(format!(
+                        "Move Overhead expects a time between 0 and {} ms",
+                        MAX_MOVE_OVERHEAD.as_millis()
+                    ))
+                })?;
+            Ok(())
+        } else {
+            Err(DiogenesError::InvalidUciError(format!(
+                "unknown option {name:?}"
+            )))
+        }
+    }
+
+    /// Handles `position [startpos | fen <fen>] [moves <move>...]`. The
+    /// current position is only replaced if the whole command is valid.
+    fn set_position(&mut self, args: &str) -> DiogenesResult<()> {
+        let (setup, moves) = match args.split_once("moves") {
+            Some((setup, moves)) => (setup.trim(), moves),
+            None => (args.trim(), ""),
+        };
+        let mut position = if setup == "startpos" {
+            Position::from_str(STARTING_FEN)?
+        } else if let Some(fen) = setup.strip_prefix("fen ") {
+            Position::from_str(fen)?
+        } else {
+            return Err(DiogenesError::InvalidUciError(format!(
+                "expected startpos or fen but found {setup:?}"
+            )));
+        };
+
+        for text in moves.split_whitespace() {
+            let mv = position.parse_uci_move(text)?;
+            position.make_move(mv);
+        }
+        self.position = position;
+
+        Ok(())
+    }
+
+    /// Handles `go`, starting a search of the current position on a
+    /// background thread that reports with `info` and `bestmove` lines.
+    fn go(&mut self, args: &str) -> DiogenesResult<()> {
+        let options = GoOptions::from_str(args)?;
+        self.stop_search();
+        self.start_search(options)
+    }
+
+    /// Handles `ponderhit`: the opponent played the move the engine was
+    /// pondering on, so the clock is now running. The ponder search had no
+    /// deadlines, so it is restarted with them, with the transposition
+    /// table keeping what it found so far.
+    fn ponderhit(&mut self) -> DiogenesResult<()> {
+        let Some(mut options) = self.ponder.take() else {
+            return Ok(());
+        };
+        self.stop_search();
+        options.ponder = false;
+        self.start_search(options)
+    }
+
+    /// Starts searching the current position on a background thread, once
+    /// any previous search has been stopped.
+    fn start_search(&mut self, options: GoOptions) -> DiogenesResult<()> {
+        let mut position = self.position.clone();
+        if let Some(depth) = options.perft {
+            return self.perft(&mut position, depth);
+        }
+
+        let limits = Limits {
+            depth: options.depth,
+            nodes: options.nodes,
+            time: Self::time_manager(&options, &position, self.move_overhead),
+            mate: options.mate,
+            root_moves: options
+                .searchmoves
+                .iter()
+                .map(|text| position.parse_uci_move(text))
+                .collect::<DiogenesResult<_>>()?,
+        };
+        self.release.set_pondering(options.ponder);
+        self.ponder = options.ponder.then(|| options.clone());
+        let infinite = options.infinite;
+        let release = Arc::clone(&self.release);
+        let output = Arc::clone(&self.output);
+        let stop = Arc::clone(&self.stop);
+        let tt = Arc::clone(&self.tt);
+        self.worker = Some(thread::spawn(move || {
+            Self::search(position, limits, infinite, release, stop, tt, output)
+        }));
+
+        Ok(())
+    }
+
+    /// Returns the deadlines for the search from the clock of the side to
+    /// move, or [`None`] if the search is not limited by time. Infinite and
+    /// ponder searches ignore the clock until `stop` or `ponderhit`.
+    fn time_manager(
+        options: &GoOptions,
+        position: &Position,
+        overhead: Duration,
+    ) -> Option<TimeManager> {
+        if options.infinite || options.ponder {
+            return None;
+        }
+        if let Some(movetime) = options.movetime {
+            return Some(TimeManager::fixed(
+                Duration::from_millis(movetime),
+                overhead,
+            ));
+        }
+
+        let (time, inc) = match position.side_to_move() {
+            Color::White => (options.wtime?, options.winc.unwrap_or(0)),
+            Color::Black => (options.btime?, options.binc.unwrap_or(0)),
+        };
+        Some(TimeManager::clock(
+            Duration::from_millis(time),
+            Duration::from_millis(inc),
+            options.movestogo,
+            overhead,
+        ))
+    }
+
+    /// Runs the search for a single `go` command on the worker thread,
+    /// streaming an `info` line per iteration and finishing with `bestmove`.
+    ///
+    /// The protocol forbids sending `bestmove` during an `infinite` search
+    /// before `stop`, or while pondering before `stop` or `ponderhit`, so
+    /// the worker waits for them then. A search stopped while it is still
+    /// pondering sends nothing, since it is abandoned or restarted.
+    fn search(
+        mut position: Position,
+        limits: Limits,
+        infinite: bool,
+        release: Arc<Release>,
+        stop: Arc<AtomicBool>,
+        tt: Arc<TranspositionTable>,
+        output: Output,
+    ) {
+        let mut searcher = Searcher::new(limits, Arc::clone(&stop), Arc::clone(&tt));
+        let best = searcher.search(&mut position, |iteration| {
+            send(&output, &Self::info(iteration, tt.hashfull()))
+        });
+
+        if !release.wait(infinite, &stop) {
+            return;
+        }
+        let best = best.best_move().unwrap_or(Move::NULL);
+        send(&output, &format!("bestmove {best}"));
+    }
+
+    /// Formats an `info` line describing a completed search iteration, with
+    /// `hashfull` being the permille of the transposition table in use.
+    fn info(iteration: &Iteration, hashfull: usize) -> String {
+        let score = match mate_in(iteration.score) {
+            Some(moves) => format!("mate {moves}"),
+            None => format!("cp {}", iteration.score),
+        };
+        let millis = iteration.time.as_millis();
+        let nps = (iteration.nodes as u128 * 1000) / millis.max(1);
+        let pv: Vec<String> = iteration.pv.iter().map(Move::to_string).collect();
+
+        format!(
+            "info depth {} score {score} nodes {} nps {nps} hashfull {hashfull} time {millis} pv {}",
+            iteration.depth,
+            iteration.nodes,
+            pv.join(" ")
+        )
+    }
+
+    /// Handles the non-standard `go perft <depth>`, printing the node count
+    /// below each legal move as most engines do.
+    fn perft(&self, position: &mut Position, depth: u32) -> DiogenesResult<()> {
+        let divide = position.divide(depth);
+        for (mv, nodes) in &divide {
+            send(&self.output, &format!("{mv}: {nodes}"));
+        }
+        let total: u64 = divide.iter().map(|(_, nodes)| nodes).sum();
+        send(&self.output, &format!("\nNodes searched: {total}"));
+
+        Ok(())
+    }
+
+    /// Stops a running search, waiting for it to send its `bestmove` unless
+    /// it was still pondering.
+    fn stop_search(&mut self) {
+        self.ponder = None;
+        if let Some(worker) = self.worker.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            self.release.notify();
+            worker.join().expect("search thread panicked");
+        }
+        self.stop.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use rstest::rstest;
+
+    use crate::position::Position;
+    use crate::search::MATE;
+    use crate::timeman::{DEFAULT_MOVE_OVERHEAD, TimeManager};
+    use crate::tt::DEFAULT_HASH_MB;
+
+    use super::{GoOptions, Uci};
+
+    /// A [`Write`] whose contents can be inspected after it is handed to [`Uci`].
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn lines(&self) -> Vec<String> {
+            let bytes = self.0.lock().unwrap();
+            String::from_utf8_lossy(&bytes)
+                .lines()
+                .map(str::to_string)
+                .collect()
+        }
+
+        /// Waits for the engine to send a line starting with `prefix`,
+        /// returning everything sent up to and including it.
+        fn wait_for(&self, prefix: &str) -> Vec<String> {
+            let start = Instant::now();
+            loop {
+                let lines = self.lines();
+                if let Some(index) = lines.iter().position(|line| line.starts_with(prefix)) {
+                    return lines[..=index].to_vec();
+                }
+                assert!(
+                    start.elapsed() < Duration::from_secs(10),
+                    "no {prefix:?} in {lines:?}"
+                );
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+
+    fn run(commands: &str) -> (Uci, Vec<String>) {
+        let buffer = Buffer::default();
+        let mut uci = Uci::new(buffer.clone());
+        for line in commands.lines() {
+            uci.handle(line);
+        }
+        // Let any search finish on its own rather than stopping it.
+        if let Some(worker) = uci.worker.take() {
+            worker.join().unwrap();
+        }
+        (uci, buffer.lines())
+    }
+
+    #[test]
+    fn test_handshake() {
+        let buffer = Buffer::default();
+        let mut uci = Uci::new(buffer.clone());
+        uci.run(Cursor::new("uci\nisready\nquit\nisready\n"))
+            .unwrap();
+
+        let lines = buffer.lines();
+        assert!(lines[0].starts_with("id name Diogenes"));
+        assert!(lines[1].starts_with("id author"));
+        assert!(lines[2].starts_with("option name Hash type spin"));
+        assert!(lines[3].starts_with("option name Move Overhead type spin"));
+        assert_eq!(lines[4..], ["uciok", "readyok"]);
+    }
+
+    #[rstest]
+    #[case::startpos("position startpos", Position::default().to_string())]
+    #[case::startpos_moves(
+        "position startpos moves e2e4 c7c5 g1f3",
+        "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    )]
+    #[case::fen(
+        "position fen 4k3/P7/8/8/8/8/8/4K3 w - - 0 1",
+        "4k3/P7/8/8/8/8/8/4K3 w - - 0 1"
+    )]
+    #[case::fen_moves_promotion(
+        "position fen 4k3/P7/8/8/8/8/8/4K3 w - - 0 1 moves a7a8q e8d7",
+        "Q7/3k4/8/8/8/8/8/4K3 w - - 1 2"
+    )]
+    #[case::castling(
+        "position fen r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1 moves e1g1 e8c8",
+        "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2"
+    )]
+    fn test_position(#[case] command: &str, #[case] expected: impl AsRef<str>) {
+        let (uci, lines) = run(command);
+        assert!(lines.is_empty(), "unexpected output {lines:?}");
+        assert_eq!(expected.as_ref(), uci.position().to_string());
+    }
+
+    #[rstest]
+    #[case::illegal_move("position startpos moves e2e5")]
+    #[case::invalid_fen("position fen 4k3/8/8/8/8/8/8/8 w - - 0 1")]
+    #[case::missing_setup("position moves e2e4")]
+    #[case::unknown_command("castle")]
+    fn test_invalid_command_is_reported(#[case] command: &str) {
+        let (uci, lines) = run(&format!("position startpos moves d2d4\n{command}"));
+        assert_eq!(1, lines.len());
+        assert!(lines[0].starts_with("info string"), "{lines:?}");
+        assert_eq!(
+            "rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 1",
+            uci.position().to_string(),
+            "an invalid command must leave the position untouched"
+        );
+    }
+
+    #[test]
+    fn test_go_sends_bestmove() {
+        let (_, lines) = run("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 3\n");
+        assert!(lines[0].starts_with("info depth 1 score "), "{lines:?}");
+        assert!(
+            lines.iter().any(|l| l.contains("score mate 1")),
+            "{lines:?}"
+        );
+        assert_eq!("bestmove a1a8", lines.last().unwrap());
+    }
+
+    #[test]
+    fn test_go_reports_hashfull() {
+        let (_, lines) = run("go depth 4\n");
+        let hashfull: Vec<usize> = lines
+            .iter()
+            .filter_map(|l| {
+                l.split_once(" hashfull ")?
+                    .1
+                    .split(' ')
+                    .next()?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        assert_eq!(4, hashfull.len(), "{lines:?}");
+        assert!(hashfull.iter().all(|&permille| permille <= 1000));
+    }
+
+    #[rstest]
+    #[case::resize("setoption name Hash value 1", 1024 * 1024)]
+    #[case::case_insensitive("setoption name hash value 2", 2 * 1024 * 1024)]
+    fn test_setoption_hash(#[case] command: &str, #[case] size: usize) {
+        let (uci, lines) = run(&format!("go depth 2\n{command}\ngo depth 2"));
+        assert!(
+            !lines.iter().any(|l| l.starts_with("info string")),
+            "{lines:?}"
+        );
+        assert_eq!(size, uci.tt.size());
+    }
+
+    #[rstest]
+    #[case::missing_name("setoption Hash value 1")]
+    #[case::missing_value("setoption name Hash")]
+    #[case::too_small("setoption name Hash value 0")]
+    #[case::unknown_option("setoption name Threads value 4")]
+    fn test_invalid_setoption_is_reported(#[case] command: &str) {
+        let (uci, lines) = run(command);
+        assert_eq!(1, lines.len());
+        assert!(lines[0].starts_with("info string"), "{lines:?}");
+        assert_eq!(DEFAULT_HASH_MB * 1024 * 1024, uci.tt.size());
+    }
+
+    #[test]
+    fn test_go_searchmoves() {
+        let (_, lines) = run("go depth 2 searchmoves a2a3\n");
+        assert_eq!("bestmove a2a3", lines.last().unwrap());
+
+        let (_, lines) = run("go depth 2 searchmoves a2a5\n");
+        assert!(lines[0].starts_with("info string"), "{lines:?}");
+    }
+
+    #[test]
+    fn test_go_searchmoves_leaves_root_out_of_table() {
+        let fen = "6k1/5ppp/8/8/8/8/1P6/R5K1 w - - 0 1";
+        let (uci, lines) = run(&format!(
+            "position fen {fen}\ngo depth 2 searchmoves b2b3\n"
+        ));
+        assert_eq!("bestmove b2b3", lines.last().unwrap());
+        assert_eq!(None, uci.tt.probe(uci.position.hash(), 0));
+
+        let (uci, _) = run(&format!("position fen {fen}\ngo depth 2\n"));
+        let entry = uci.tt.probe(uci.position.hash(), 0).unwrap();
+        assert_eq!(MATE - 1, entry.score);
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[rstest]
+    #[case::movetime("movetime 1500", "w", Some(TimeManager::fixed(ms(1500), ms(10))))]
+    #[case::white_clock(
+        "wtime 60000 btime 1000",
+        "w",
+        Some(TimeManager::clock(ms(60000), ms(0), None, ms(10)))
+    )]
+    #[case::black_clock(
+        "wtime 60000 btime 30000 winc 2000 binc 1000 movestogo 5",
+        "b",
+        Some(TimeManager::clock(ms(30000), ms(1000), Some(5), ms(10)))
+    )]
+    #[case::other_clock("btime 30000", "w", None)]
+    #[case::no_clock("depth 5", "w", None)]
+    #[case::infinite("wtime 60000 btime 60000 infinite", "w", None)]
+    #[case::ponder("wtime 60000 btime 60000 ponder", "w", None)]
+    fn test_time_manager(
+        #[case] args: &str,
+        #[case] side: &str,
+        #[case] expected: Option<TimeManager>,
+    ) {
+        let options = GoOptions::from_str(args).unwrap();
+        let position = Position::from_str(&format!("4k3/8/8/8/8/8/8/4K3 {side} - - 0 1")).unwrap();
+        assert_eq!(expected, Uci::time_manager(&options, &position, ms(10)));
+    }
+
+    #[test]
+    fn test_setoption_move_overhead() {
+        let (uci, lines) = run("setoption name Move Overhead value 100");
+        assert!(lines.is_empty(), "{lines:?}");
+        assert_eq!(ms(100), uci.move_overhead);
+
+        let (uci, lines) = run("setoption name Move Overhead value 100000");
+        assert!(lines[0].starts_with("info string"), "{lines:?}");
+        assert_eq!(DEFAULT_MOVE_OVERHEAD, uci.move_overhead);
+    }
+
+    #[test]
+    fn test_go_with_clock_sends_bestmove() {
+        let (_, lines) = run("go wtime 100 btime 100\n");
+        assert!(lines.last().unwrap().starts_with("bestmove "), "{lines:?}");
+    }
+
+    #[test]
+    fn test_go_infinite_waits_for_stop() {
+        let buffer = Buffer::default();
+        let mut uci = Uci::new(buffer.clone());
+        uci.handle("go infinite depth 2");
+        // The search is over once its last iteration is reported.
+        buffer.wait_for("info depth 2");
+        let lines = buffer.lines();
+        assert!(
+            !lines.iter().any(|l| l.starts_with("bestmove")),
+            "{lines:?}"
+        );
+
+        uci.handle("stop");
+        assert!(buffer.lines().last().unwrap().starts_with("bestmove"));
+    }
+
+    #[test]
+    fn test_go_ponder_waits_for_ponderhit() {
+        let buffer = Buffer::default();
+        let mut uci = Uci::new(buffer.clone());
+        uci.handle("go ponder depth 2");
+        // The search is over once its last iteration is reported.
+        buffer.wait_for("info depth 2");
+        let lines = buffer.lines();
+        assert!(
+            !lines.iter().any(|l| l.starts_with("bestmove")),
+            "{lines:?}"
+        );
+
+        uci.handle("ponderhit");
+        buffer.wait_for("bestmove");
+    }
+
+    #[test]
+    fn test_ponderhit_starts_clock() {
+        let buffer = Buffer::default();
+        let mut uci = Uci::new(buffer.clone());
+        uci.handle("go ponder wtime 100 btime 100");
+        buffer.wait_for("info depth 1");
+        uci.handle("ponderhit");
+        let lines = buffer.wait_for("bestmove");
+        assert_eq!(
+            1,
+            lines.iter().filter(|l| l.starts_with("bestmove")).count()
+        );
+    }
+
+    #[test]
+    fn test_stop_while_pondering_sends_bestmove() {
+        let buffer = Buffer::default();
+        let mut uci = Uci::new(buffer.clone());
+        uci.handle("go ponder");
+        buffer.wait_for("info depth 1");
+        uci.handle("stop");
+        assert!(buffer.lines().last().unwrap().starts_with("bestmove"));
+
+        // The search is over, so there is nothing left to restart.
+        uci.handle("ponderhit");
+        assert!(uci.worker.is_none());
+    }
+
+    #[rstest]
+    #[case::only_move("7k/8/8/8/8/8/1q6/K7 w - - 0 1", "bestmove a1b2")]
+    #[case::stalemate("7k/8/8/8/8/8/2q5/K7 w - - 0 1", "bestmove 0000")]
+    fn test_go_forced(#[case] fen: &str, #[case] expected: &str) {
+        let (_, lines) = run(&format!("position fen {fen}\ngo depth 2\n"));
+        assert_eq!(expected, lines.last().unwrap());
+    }
+
+    #[test]
+    fn test_go_perft() {
+        let (_, lines) = run("go perft 2\n");
+        assert_eq!(22, lines.len());
+        assert!(lines[..20].iter().all(|l| l.ends_with(": 20")));
+        assert_eq!("Nodes searched: 400", lines.last().unwrap());
+    }
+
+    #[rstest]
+    #[case::empty("", GoOptions::default())]
+    #[case::clock(
+        "wtime 300000 btime -20 winc 2000 binc 2000 movestogo 40",
+        GoOptions {
+            wtime: Some(300000),
+            btime: Some(0),
+            winc: Some(2000),
+            binc: Some(2000),
+            movestogo: Some(40),
+            ..GoOptions::default()
+        }
+    )]
+    #[case::fixed(
+        "depth 8 nodes 100000 movetime 5000 mate 3",
+        GoOptions {
+            depth: Some(8),
+            nodes: Some(100000),
+            movetime: Some(5000),
+            mate: Some(3),
+            ..GoOptions::default()
+        }
+    )]
+    #[case::searchmoves(
+        "searchmoves e2e4 d2d4 infinite",
+        GoOptions {
+            searchmoves: vec!["e2e4".into(), "d2d4".into()],
+            infinite: true,
+            ..GoOptions::default()
+        }
+    )]
+    fn test_parse_go_options(#[case] args: &str, #[case] expected: GoOptions) {
+        assert_eq!(Ok(expected), GoOptions::from_str(args));
+    }
+
+    #[rstest]
+    #[case::missing_value("depth")]
+    #[case::not_a_number("nodes many")]
+    #[case::unknown("fast")]
+    fn test_parse_invalid_go_options(#[case] args: &str) {
+        assert!(GoOptions::from_str(args).is_err());
+    }
+}
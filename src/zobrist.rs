@@ -0,0 +1,144 @@
+//! Zobrist hashing of positions.
+//!
+//! Every feature of a position (a piece on a square, the side to move, each
+//! castling right and the en-passant file, if a capture there is possible)
+//! is assigned a random 64-bit key, and a position's hash is the XOR of the
+//! keys of the features present.
+//! Since XOR is its own inverse, making a move only needs to XOR in and out
+//! the keys of the features it changes.
+//! See <https://www.chessprogramming.org/Zobrist_Hashing>
+
+use num_traits::ToPrimitive;
+
+use crate::castling::CastlingRights;
+use crate::piece::Piece;
+use crate::square::Square;
+
+/// The random keys for every hashed feature of a position.
+pub struct ZobristKeys {
+    pieces: [[u64; 64]; 12],
+    side: u64,
+    castling: [u64; 4],
+    ep: [u64; 8],
+}
+
+/// Keys generated at compile time from a fixed seed, so hashes are stable
+/// across runs and builds.
+pub static KEYS: ZobristKeys = ZobristKeys::new(0x2545f4914f6cdd1d);
+
+impl ZobristKeys {
+    const fn new(seed: u64) -> Self {
+        let mut state = seed;
+        let mut keys = ZobristKeys {
+            pieces: [[0; 64]; 12],
+            side: 0,
+            castling: [0; 4],
+            ep: [0; 8],
+        };
+
+        let mut piece = 0;
+        while piece < 12 {
+            let mut sq = 0;
+            while sq < 64 {
+                keys.pieces[piece][sq] = splitmix64(&mut state);
+                sq += 1;
+            }
+            piece += 1;
+        }
+
+        keys.side = splitmix64(&mut state);
+
+        let mut i = 0;
+        while i < 4 {
+            keys.castling[i] = splitmix64(&mut state);
+            i += 1;
+        }
+
+        let mut i = 0;
+        while i < 8 {
+            keys.ep[i] = splitmix64(&mut state);
+            i += 1;
+        }
+
+        keys
+    }
+
+    /// The key for `piece` standing on `sq`.
+    pub fn piece(&self, piece: Piece, sq: Square) -> u64 {
+        self.pieces[usize::from(piece)][sq as usize]
+    }
+
+    /// The key toggled whenever the side to move changes. It is present in
+    /// the hash when black is to move.
+    pub fn side(&self) -> u64 {
+        self.side
+    }
+
+    /// The combined key of every right held in `rights`.
+    pub fn castling(&self, rights: CastlingRights) -> u64 {
+        let bits = rights.raw_value();
+        (0..4)
+            .filter(|i| bits & (1 << i) != 0)
+            .fold(0, |key, i| key ^ self.castling[i])
+    }
+
+    /// The key for the en-passant square `ep`, if any. Only the file is
+    /// hashed since the rank is implied by the side to move.
+    pub fn ep(&self, ep: Option<Square>) -> u64 {
+        ep.map_or(0, |sq| {
+            self.ep[sq.file().to_usize().expect("files fit in a usize")]
+        })
+    }
+}
+
+/// A small, fast pseudo-random number generator which is good enough to
+/// produce well-distributed keys and can be evaluated at compile time.
+/// See <https://prng.di.unimi.it/splitmix64.c>
+const fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use strum::IntoEnumIterator;
+
+    use crate::{castling::CastlingRights, piece::Piece, square::Square};
+
+    use super::KEYS;
+
+    #[test]
+    fn test_keys_are_unique() {
+        let mut keys: HashSet<u64> = HashSet::new();
+        for piece in Piece::iter() {
+            for sq in Square::iter() {
+                assert!(keys.insert(KEYS.piece(piece, sq)));
+            }
+        }
+        assert!(keys.insert(KEYS.side()));
+        for i in 0..4 {
+            let rights = CastlingRights::new_with_raw_value(1 << i);
+            assert!(keys.insert(KEYS.castling(rights)));
+        }
+        for sq in [Square::A3, Square::B3, Square::C3, Square::D3] {
+            assert!(keys.insert(KEYS.ep(Some(sq))));
+        }
+    }
+
+    #[test]
+    fn test_castling_key_combines_rights() {
+        let all = CastlingRights::default();
+        let white = CastlingRights::new_with_raw_value(0b1100);
+        let black = CastlingRights::new_with_raw_value(0b0011);
+        assert_eq!(
+            KEYS.castling(all),
+            KEYS.castling(white) ^ KEYS.castling(black)
+        );
+        assert_eq!(0, KEYS.castling(CastlingRights::ZERO));
+    }
+}
@@ -0,0 +1,49 @@
+use std::str::FromStr;
+
+use diogenes::position::Position;
+
+/// The standard perft positions along with their known node counts.
+/// See <https://www.chessprogramming.org/Perft_Results>
+const SUITE: &str = include_str!("data/perft.epd");
+
+/// Debug builds are far too slow to walk the deeper trees, so only counts up
+/// to this size are checked unless the suite is run with `--release`.
+const DEBUG_NODE_LIMIT: u64 = 100_000;
+
+/// Parses a line of the form `<fen> ;D1 <nodes> ;D2 <nodes> ...`.
+fn parse_line(line: &str) -> (&str, Vec<(u32, u64)>) {
+    let mut fields = line.split(';');
+    let fen = fields.next().expect("line has a FEN").trim();
+    let counts = fields
+        .map(|field| {
+            let (depth, nodes) = field
+                .trim()
+                .split_once(' ')
+                .expect("field is of the form D<depth> <nodes>");
+            let depth = depth
+                .trim_start_matches('D')
+                .parse()
+                .expect("depth is an integer");
+            let nodes = nodes.parse().expect("node count is an integer");
+            (depth, nodes)
+        })
+        .collect();
+
+    (fen, counts)
+}
+
+#[test]
+fn test_perft_suite() {
+    for line in SUITE.lines().filter(|line| !line.trim().is_empty()) {
+        let (fen, counts) = parse_line(line);
+        let mut pos = Position::from_str(fen).expect("suite FEN is valid");
+
+        for (depth, expected) in counts {
+            if cfg!(debug_assertions) && expected > DEBUG_NODE_LIMIT {
+                continue;
+            }
+
+            assert_eq!(expected, pos.perft(depth), "perft({depth}) of {fen}");
+        }
+    }
+}